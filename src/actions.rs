@@ -1,11 +1,16 @@
 use tokio::sync::mpsc;
+use chrono::{Local, TimeDelta, Timelike};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::app::App;
-use crate::pagerduty::{acknowledge_async, get_items_async, PAGER_DUTY_INCIDENT_URL};
-use crate::utils::open_in_browser;
+use crate::app::{App, ConfirmAction, Confirmation, Mode, OverrideForm, PickerPurpose, Tab, ONCALL_DAYS, SCHEDULE_DAYS, DURATIONS};
+use crate::sort::SortColumn;
+use crate::pagerduty::{acknowledge_async, add_note_async, bulk_acknowledge_async, bulk_reassign_async, bulk_resolve_async, create_maintenance_window_async, create_override_async, delete_override_async, end_maintenance_window_async, escalate_async, get_assignees_async, get_details_async, get_items_async, get_oncalls_async, get_schedules_async, reassign_async, resolve_async, snooze_async, Assignee, Incident, Mutation};
+use crate::utils::{open_in_browser, parse_duration, parse_local_time, TIME_INPUT_FORMAT};
 
 #[derive(PartialEq)]
 pub enum Action {
+  Key(KeyEvent),
+  Mouse(MouseEvent),
   UpdateIncidents,
   Increment,
   Decrement,
@@ -13,8 +18,73 @@ pub enum Action {
   Open,
   Acknowledge,
   AcknowledgeAllService,
+  Resolve,
+  ResolveAllService,
   HideAck,
   AllIncidents,
+  ShowDetails,
+  CloseDetails,
+  ScrollDetailsDown,
+  ScrollDetailsUp,
+  ComposeNote,
+  SubmitNote,
+  CancelNote,
+  Reassign,
+  Escalate,
+  PickerNext,
+  PickerPrevious,
+  PickerSelect,
+  CancelPicker,
+  Snooze,
+  DurationNext,
+  DurationPrevious,
+  SnoozeSelect,
+  CancelSnooze,
+  Search,
+  ApplySearch,
+  ClearSearch,
+  ToggleMark,
+  MarkRange,
+  MarkVisible,
+  ClearMarks,
+  ClearServiceFilter,
+  ConfirmYes,
+  ConfirmNo,
+  NextTab,
+  UpdateOnCalls,
+  ScrollOnCallsDown,
+  ScrollOnCallsUp,
+  AllOnCalls,
+  UpdateServices,
+  NextService,
+  PreviousService,
+  ShowServiceIncidents,
+  Maintenance,
+  MaintenanceSelect,
+  EndMaintenance,
+  EndMaintenanceNext,
+  EndMaintenancePrevious,
+  EndMaintenanceSelect,
+  CancelMaintenance,
+  UpdateSchedules,
+  NextSchedule,
+  PreviousSchedule,
+  OpenSchedule,
+  ToggleTimelineDays,
+  CreateOverride,
+  NextOverrideField,
+  SubmitOverride,
+  CancelOverride,
+  DeleteOverride,
+  OverrideNext,
+  OverridePrevious,
+  OverrideSelect,
+  SortNext,
+  ToggleSortOrder,
+  SortBy(SortColumn),
+  InputChar(char),
+  InputNewLine,
+  InputBackspace,
   Quit,
   None,
 }
@@ -24,9 +94,11 @@ pub const REFRESH_RATE:i64 = 250;
 
 pub async fn update(app: &mut App, msg: Action) -> Action {
   match msg {
+    Action::Key(key) => return handle_key(app, key),
+    Action::Mouse(mouse) => return handle_mouse(app, mouse),
     Action::UpdateIncidents => {
       app.refreshing = true;
-      let _res = get_items_async(app.pager_duty.get_pagerduty_domain(),app.pager_duty.get_pagerduty_api_key(), app.all_incidents, app.items_tx.clone()).await;
+      get_items_async(&app.pager_duty, app.all_incidents, app.items_tx.clone()).await;
     },
     Action::Increment => {
       app.next();
@@ -38,45 +110,414 @@ pub async fn update(app: &mut App, msg: Action) -> Action {
       app.top();
     }
     Action::Open => {
-      let selected_id = app.state.selected().unwrap();
-      let url = format!("https://{}{}{}", app.pager_duty.get_pagerduty_domain(), PAGER_DUTY_INCIDENT_URL, app.items[selected_id].id());
-      open_in_browser(&url);
+      if let Some(incident_id) = app.current_incident_id() {
+        // Link sent by PagerDuty when the incident is in the list
+        let url = app.items.iter()
+          .find(|item| item.id == incident_id && !item.html_url.is_empty())
+          .map(|item| item.html_url.clone())
+          .unwrap_or_else(|| app.pager_duty.incident_url(&incident_id));
+        open_in_browser(&url);
+      }
     },
     Action::Acknowledge => {
-      let selected_id = app.state.selected().unwrap();
-      if app.items[selected_id].triggered {
-        let selected_item:&str = app.items[selected_id].id();
-        acknowledge_async(&app.pager_duty.get_pagerduty_api_key(), selected_item).await.expect("Error during aknowledge");
-        app.items[selected_id].status = format!("{}\nSending Ack", app.items[selected_id].status);
-        app.items[selected_id].triggered = false;
-      }
+      let items_to_ack = app.target_ids_where(|item| item.triggered());
+      confirm_or_run(app, ConfirmAction::Acknowledge, items_to_ack).await;
     },
     Action::AcknowledgeAllService => {
-      // Loop on all incidents
-      for i in 0..app.items.len() {
-        if app.items[i].service == app.items[app.state.selected().unwrap()].service && app.items[i].triggered {
-          let item_to_ack: &str = app.items[i].id();
-          acknowledge_async(&app.pager_duty.get_pagerduty_api_key(), item_to_ack).await.expect("Error during aknowledge");
-          app.items[i].status = format!("{}\nSending Ack", app.items[i].status);
-          app.items[i].triggered = false;
+      let items_to_ack = service_incident_ids(app, |item| item.triggered());
+      confirm_or_run(app, ConfirmAction::Acknowledge, items_to_ack).await;
+    },
+    Action::Resolve => {
+      let items_to_resolve = app.target_ids();
+      confirm_or_run(app, ConfirmAction::Resolve, items_to_resolve).await;
+    },
+    Action::ResolveAllService => {
+      let items_to_resolve = service_incident_ids(app, |_| true);
+      confirm_or_run(app, ConfirmAction::Resolve, items_to_resolve).await;
+    },
+    Action::HideAck => {
+      app.hide_ack = !app.hide_ack;
+      app.update_view();
+    },
+    Action::AllIncidents => {
+      app.all_incidents = !app.all_incidents;
+      // Incidents of the others are not new
+      app.scope_changed = true;
+      get_items_async(&app.pager_duty, app.all_incidents, app.items_tx.clone()).await;
+      app.refreshing = true;
+    },
+    Action::ShowDetails => {
+      if let Some(item) = app.selected_item() {
+        get_details_async(&app.pager_duty, item.id(), app.details_tx.clone()).await;
+        app.details_id = Some(item.id.clone());
+        app.mode = Mode::Detail;
+        app.details = None;
+        app.details_scroll = 0;
+      }
+    },
+    Action::CloseDetails => {
+      app.mode = Mode::Normal;
+      app.details = None;
+      app.details_id = None;
+    },
+    Action::ScrollDetailsDown => {
+      app.details_scroll = app.details_scroll.saturating_add(1);
+    },
+    Action::ScrollDetailsUp => {
+      app.details_scroll = app.details_scroll.saturating_sub(1);
+    },
+    Action::ComposeNote => {
+      app.target_incident_ids = app.target_ids();
+      if !app.target_incident_ids.is_empty() {
+        app.input.clear();
+        app.mode = Mode::Note;
+      }
+    },
+    Action::SubmitNote if !app.input.trim().is_empty() => {
+      let incident_ids = std::mem::take(&mut app.target_incident_ids);
+      let content = std::mem::take(&mut app.input);
+      app.mode = if app.details_id.is_some() && app.tab == Tab::Incidents { Mode::Detail } else { Mode::Normal };
+      confirm_or_run(app, ConfirmAction::Note(content), incident_ids).await;
+    },
+    Action::CancelNote => {
+      app.input.clear();
+      app.mode = if app.details_id.is_some() && app.tab == Tab::Incidents { Mode::Detail } else { Mode::Normal };
+    },
+    Action::Reassign => {
+      app.target_incident_ids = app.target_ids();
+      if !app.target_incident_ids.is_empty() {
+        app.input.clear();
+        app.picker.title = match app.target_incident_ids.len() {
+          1 => String::from(" - Reassign to - "),
+          count => format!(" - Reassign {} incidents to - ", count),
+        };
+        app.picker.purpose = PickerPurpose::Reassign;
+        app.picker.state.select(Some(0));
+        app.mode = Mode::Picker;
+        // Users and escalation policies are only loaded once
+        if !matches!(app.picker.entries, Some(Ok(_))) {
+          app.picker.entries = None;
+          get_assignees_async(&app.pager_duty, app.assignees_tx.clone()).await;
         }
       }
     },
-    Action::HideAck => {
-      if app.hide_ack {
-        app.hide_ack = false;
+    Action::Escalate => {
+      let incident_ids = app.current_incident_id().into_iter().collect();
+      confirm_or_run(app, ConfirmAction::Escalate, incident_ids).await;
+    },
+    Action::PickerNext => {
+      app.picker.next(&app.input);
+    },
+    Action::PickerPrevious => {
+      app.picker.previous(&app.input);
+    },
+    Action::PickerSelect => {
+      if let Some(assignee) = app.picker.selected(&app.input).cloned() {
+        app.input.clear();
+        if app.picker.purpose == PickerPurpose::Override {
+          open_override_form(app, assignee);
+        } else {
+          let incident_ids = std::mem::take(&mut app.target_incident_ids);
+          app.mode = if app.details_id.is_some() { Mode::Detail } else { Mode::Normal };
+          confirm_or_run(app, ConfirmAction::Reassign(assignee), incident_ids).await;
+        }
+      }
+    },
+    Action::CancelPicker => {
+      app.input.clear();
+      app.mode = if app.details_id.is_some() { Mode::Detail } else { Mode::Normal };
+    },
+    Action::Snooze => {
+      // Only acknowledged incidents can be snoozed
+      app.target_incident_ids = app.target_ids_where(|item| !item.triggered());
+      if !app.target_incident_ids.is_empty() {
+        app.input.clear();
+        app.duration_state.select(Some(0));
+        app.mode = Mode::Snooze;
+      }
+    },
+    Action::DurationNext => {
+      let i = app.duration_state.selected().map_or(0, |i| (i + 1) % (DURATIONS.len() + 1));
+      app.duration_state.select(Some(i));
+    },
+    Action::DurationPrevious => {
+      let i = app.duration_state.selected().map_or(0, |i| if i == 0 { DURATIONS.len() } else { i - 1 });
+      app.duration_state.select(Some(i));
+    },
+    Action::SnoozeSelect => {
+      if let Some(duration) = selected_duration(app) {
+        let incident_ids = std::mem::take(&mut app.target_incident_ids);
+        app.input.clear();
+        app.mode = Mode::Normal;
+        confirm_or_run(app, ConfirmAction::Snooze(duration), incident_ids).await;
+      }
+    },
+    Action::CancelSnooze => {
+      app.input.clear();
+      app.mode = Mode::Normal;
+    },
+    Action::Search => {
+      app.input = app.filter.clone();
+      app.mode = Mode::Search;
+    },
+    Action::ApplySearch => {
+      app.input.clear();
+      app.mode = Mode::Normal;
+    },
+    Action::SortNext => {
+      app.sort(SortColumn::next(app.sort_by), app.sort_descending);
+    },
+    Action::ToggleSortOrder => {
+      app.sort(app.sort_by, !app.sort_descending);
+    },
+    Action::SortBy(column) => {
+      // Clicking the sorted column again switches the order
+      let sort_descending = app.sort_by == Some(column) && !app.sort_descending;
+      app.sort(Some(column), sort_descending);
+    },
+    Action::ToggleMark => {
+      app.toggle_mark();
+      app.next();
+    },
+    Action::MarkRange => {
+      app.mark_range();
+    },
+    Action::MarkVisible => {
+      app.mark_visible();
+    },
+    Action::ClearMarks => {
+      app.clear_marks();
+    },
+    Action::ClearServiceFilter => {
+      app.service_filter = None;
+      app.update_view();
+    },
+    Action::NextTab => {
+      app.tab = app.tab.next();
+      // Shifts are loaded the first time the tab is shown
+      if app.tab == Tab::OnCall && app.oncalls.is_none() {
+        get_oncalls_async(&app.pager_duty, ONCALL_DAYS, app.oncalls_tx.clone()).await;
+      }
+      if app.tab == Tab::Services && app.services.is_none() {
+        app.load_services().await;
+      }
+      if app.tab == Tab::Schedules && app.schedules.is_none() {
+        get_schedules_async(&app.pager_duty, app.schedules_tx.clone()).await;
+      }
+    },
+    Action::UpdateOnCalls => {
+      app.oncalls = None;
+      get_oncalls_async(&app.pager_duty, ONCALL_DAYS, app.oncalls_tx.clone()).await;
+    },
+    Action::ScrollOnCallsDown => {
+      app.oncalls_scroll = app.oncalls_scroll.saturating_add(1);
+    },
+    Action::ScrollOnCallsUp => {
+      app.oncalls_scroll = app.oncalls_scroll.saturating_sub(1);
+    },
+    Action::AllOnCalls => {
+      app.all_oncalls = !app.all_oncalls;
+      app.oncalls_scroll = 0;
+    },
+    Action::UpdateServices => {
+      app.load_services().await;
+    },
+    Action::NextService => {
+      app.next_service();
+    },
+    Action::PreviousService => {
+      app.previous_service();
+    },
+    Action::ShowServiceIncidents => {
+      if let Some(service) = app.selected_service() {
+        // By id, other services can have the same name or a name containing this one
+        app.service_filter = Some((service.id.clone(), service.name.clone()));
+        app.filter.clear();
+        app.tab = Tab::Incidents;
+        app.update_view();
+        // The counts are for everyone, show the incidents of everyone
+        if !app.all_incidents {
+          app.all_incidents = true;
+          app.scope_changed = true;
+          get_items_async(&app.pager_duty, app.all_incidents, app.items_tx.clone()).await;
+          app.refreshing = true;
+        }
+      }
+    },
+    Action::Maintenance => {
+      // The selected service, or the service of the selected incident
+      app.maintenance_service = match app.tab {
+        Tab::Services => app.selected_service().map(|service| (service.id.clone(), service.name.clone())),
+        _ => app.selected_item().map(|item| (item.service_id.clone(), item.service.clone())),
+      };
+      if app.maintenance_service.is_some() {
+        app.input.clear();
+        app.duration_state.select(Some(0));
+        app.mode = Mode::Maintenance;
+      }
+    },
+    Action::MaintenanceSelect => {
+      if let Some(duration) = selected_duration(app) {
+        if let Some((service_id, _)) = app.maintenance_service.take() {
+          create_maintenance_window_async(&app.pager_duty, &service_id, duration, app.maintenance_outcomes_tx.clone()).await;
+        }
+        app.input.clear();
+        app.mode = Mode::Normal;
+      }
+    },
+    Action::EndMaintenance => {
+      if app.maintenance_windows().is_empty() {
+        app.error = Some(String::from("There is no maintenance window to end"));
       } else {
-        app.hide_ack = true;
+        app.maintenance_state.select(Some(0));
+        app.mode = Mode::EndMaintenance;
       }
     },
-    Action::AllIncidents => {
-      if app.all_incidents {
-        app.all_incidents = false;
+    Action::EndMaintenanceNext => {
+      let len = app.maintenance_windows().len();
+      let i = app.maintenance_state.selected().map_or(0, |i| if i + 1 >= len { 0 } else { i + 1 });
+      app.maintenance_state.select(Some(i));
+    },
+    Action::EndMaintenancePrevious => {
+      let len = app.maintenance_windows().len();
+      let i = app.maintenance_state.selected().map_or(0, |i| if i == 0 { len.saturating_sub(1) } else { i - 1 });
+      app.maintenance_state.select(Some(i));
+    },
+    Action::EndMaintenanceSelect => {
+      let window_id = app.maintenance_state.selected()
+        .and_then(|i| app.maintenance_windows().get(i))
+        .map(|window| window.id.clone());
+      app.mode = Mode::Normal;
+      if let Some(window_id) = window_id {
+        confirm_or_run(app, ConfirmAction::EndMaintenance(window_id), Vec::new()).await;
+      }
+    },
+    Action::CancelMaintenance => {
+      app.maintenance_service = None;
+      app.input.clear();
+      app.mode = Mode::Normal;
+    },
+    Action::UpdateSchedules => {
+      app.timeline = None;
+      get_schedules_async(&app.pager_duty, app.schedules_tx.clone()).await;
+    },
+    Action::NextSchedule => {
+      app.next_schedule().await;
+    },
+    Action::PreviousSchedule => {
+      app.previous_schedule().await;
+    },
+    Action::OpenSchedule => {
+      if let Some(schedule) = app.selected_schedule() {
+        open_in_browser(&app.pager_duty.schedule_url(&schedule.id));
+      }
+    },
+    Action::ToggleTimelineDays => {
+      let i = SCHEDULE_DAYS.iter().position(|days| *days == app.timeline_days).unwrap_or(0);
+      app.timeline_days = SCHEDULE_DAYS[(i + 1) % SCHEDULE_DAYS.len()];
+      app.load_timeline().await;
+    },
+    Action::CreateOverride if app.selected_schedule().is_some() => {
+      app.input.clear();
+      app.picker.title = String::from(" - Override by - ");
+      app.picker.purpose = PickerPurpose::Override;
+      app.picker.state.select(Some(0));
+      app.mode = Mode::Picker;
+      if !matches!(app.picker.entries, Some(Ok(_))) {
+        app.picker.entries = None;
+        get_assignees_async(&app.pager_duty, app.assignees_tx.clone()).await;
+      }
+    },
+    Action::NextOverrideField => {
+      if let Some(form) = &mut app.override_form {
+        form.editing_end = !form.editing_end;
+      }
+    },
+    Action::SubmitOverride => {
+      if let Some(form) = &mut app.override_form {
+        let now = Local::now();
+        match (parse_local_time(&form.start, &now), parse_local_time(&form.end, &now)) {
+          (Some(start), Some(end)) if start < end => {
+            create_override_async(&app.pager_duty, &form.schedule_id, &form.user.id, start, end, app.override_outcomes_tx.clone()).await;
+            app.override_form = None;
+            app.mode = Mode::Normal;
+          }
+          (Some(_), Some(_)) => form.error = Some(String::from("The end must be after the start")),
+          _ => form.error = Some(String::from("Times are like 2024-03-01 18:00, Fri 18:00 or 18:00")),
+        }
+      }
+    },
+    Action::CancelOverride => {
+      app.override_form = None;
+      app.mode = Mode::Normal;
+    },
+    Action::DeleteOverride => {
+      if app.own_overrides().is_empty() {
+        app.error = Some(String::from("You have no override to delete in this schedule"));
       } else {
-        app.all_incidents = true;
+        app.override_state.select(Some(0));
+        app.mode = Mode::DeleteOverride;
       }
-      let _res = get_items_async(app.pager_duty.get_pagerduty_domain(),app.pager_duty.get_pagerduty_api_key(), app.all_incidents, app.items_tx.clone()).await;
-      app.refreshing = true;
+    },
+    Action::OverrideNext => {
+      let len = app.own_overrides().len();
+      let i = app.override_state.selected().map_or(0, |i| if i + 1 >= len { 0 } else { i + 1 });
+      app.override_state.select(Some(i));
+    },
+    Action::OverridePrevious => {
+      let len = app.own_overrides().len();
+      let i = app.override_state.selected().map_or(0, |i| if i == 0 { len.saturating_sub(1) } else { i - 1 });
+      app.override_state.select(Some(i));
+    },
+    Action::OverrideSelect => {
+      let selected = app.override_state.selected()
+        .and_then(|i| app.own_overrides().get(i).and_then(|schedule_override| schedule_override.override_id.clone()));
+      let schedule_id = app.selected_schedule().map(|schedule| schedule.id.clone());
+      app.mode = Mode::Normal;
+      if let (Some(override_id), Some(schedule_id)) = (selected, schedule_id) {
+        confirm_or_run(app, ConfirmAction::DeleteOverride { schedule_id, override_id }, Vec::new()).await;
+      }
+    },
+    Action::ConfirmYes => {
+      app.mode = if app.details_id.is_some() { Mode::Detail } else { Mode::Normal };
+      if let Some(confirmation) = app.confirmation.take() {
+        run_mutation(app, confirmation.action, confirmation.incident_ids).await;
+      }
+    },
+    Action::ConfirmNo => {
+      app.confirmation = None;
+      app.mode = if app.details_id.is_some() { Mode::Detail } else { Mode::Normal };
+    },
+    Action::ClearSearch => {
+      app.input.clear();
+      app.filter.clear();
+      app.update_view();
+      app.mode = Mode::Normal;
+    },
+    Action::InputChar(c) if app.mode == Mode::OverrideForm => {
+      if let Some(form) = &mut app.override_form {
+        form.field_mut().push(c);
+      }
+    },
+    Action::InputBackspace if app.mode == Mode::OverrideForm => {
+      if let Some(form) = &mut app.override_form {
+        form.field_mut().pop();
+      }
+    },
+    Action::InputChar(c) => {
+      app.input.push(c);
+      app.picker.state.select(Some(0));
+      // Typing a duration means a custom one
+      app.duration_state.select(Some(DURATIONS.len()));
+      update_search(app);
+    },
+    Action::InputNewLine => {
+      app.input.push('\n');
+    },
+    Action::InputBackspace => {
+      app.input.pop();
+      app.picker.state.select(Some(0));
+      update_search(app);
     },
     Action::Quit => app.should_quit = true, // You can handle cleanup and exit here
     _ => {},
@@ -84,38 +525,332 @@ pub async fn update(app: &mut App, msg: Action) -> Action {
   Action::None
 }
 
+// Duration picked in the prompt, or typed as a custom one
+fn selected_duration(app: &App) -> Option<u64> {
+  match app.duration_state.selected() {
+    Some(i) if i < DURATIONS.len() => Some(DURATIONS[i].1),
+    _ => parse_duration(&app.input),
+  }
+}
+
+// Times of the override filled in with the next hour, for half a day
+fn open_override_form(app: &mut App, user: Assignee) {
+  let Some(schedule_id) = app.selected_schedule().map(|schedule| schedule.id.clone()) else {
+    app.mode = Mode::Normal;
+    return;
+  };
+  let now = Local::now();
+  let start = now.with_minute(0).and_then(|start| start.with_second(0)).unwrap_or(now) + TimeDelta::hours(1);
+  let end = start + TimeDelta::hours(12);
+  app.override_form = Some(OverrideForm {
+    schedule_id,
+    user,
+    start: start.format(TIME_INPUT_FORMAT).to_string(),
+    end: end.format(TIME_INPUT_FORMAT).to_string(),
+    editing_end: false,
+    error: None,
+  });
+  app.mode = Mode::OverrideForm;
+}
+
+// The list is filtered as the search is typed
+fn update_search(app: &mut App) {
+  if app.mode == Mode::Search {
+    app.filter = app.input.clone();
+    app.update_view();
+  }
+}
+
+// Translate a key press into an action, depending on what is on screen
+fn handle_key(app: &App, key: KeyEvent) -> Action {
+  match app.mode {
+    Mode::Normal if app.tab == Tab::OnCall => match key.code {
+      KeyCode::Tab => Action::NextTab,
+      KeyCode::Char('j') | KeyCode::Down => Action::ScrollOnCallsDown,
+      KeyCode::Char('k') | KeyCode::Up => Action::ScrollOnCallsUp,
+      KeyCode::Char('r') | KeyCode::F(5) => Action::UpdateOnCalls,
+      KeyCode::Char('g') => Action::AllOnCalls,
+      KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+
+      _ => Action::None,
+    },
+    Mode::Normal if app.tab == Tab::Services => match key.code {
+      KeyCode::Tab => Action::NextTab,
+      KeyCode::Char('j') | KeyCode::Down => Action::NextService,
+      KeyCode::Char('k') | KeyCode::Up => Action::PreviousService,
+      KeyCode::Char('r') | KeyCode::F(5) => Action::UpdateServices,
+      KeyCode::Enter => Action::ShowServiceIncidents,
+      KeyCode::Char('m') => Action::Maintenance,
+      KeyCode::Char('e') => Action::EndMaintenance,
+      KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+
+      _ => Action::None,
+    },
+    Mode::Normal if app.tab == Tab::Schedules => match key.code {
+      KeyCode::Tab => Action::NextTab,
+      KeyCode::Char('j') | KeyCode::Down => Action::NextSchedule,
+      KeyCode::Char('k') | KeyCode::Up => Action::PreviousSchedule,
+      KeyCode::Char('r') | KeyCode::F(5) => Action::UpdateSchedules,
+      KeyCode::Char('o') | KeyCode::Enter => Action::OpenSchedule,
+      KeyCode::Char('w') => Action::ToggleTimelineDays,
+      KeyCode::Char('c') => Action::CreateOverride,
+      KeyCode::Char('x') => Action::DeleteOverride,
+      KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+
+      _ => Action::None,
+    },
+    Mode::Normal => match key.code {
+      KeyCode::Tab => Action::NextTab,
+      KeyCode::Char('j') | KeyCode::Down => Action::Increment,
+      KeyCode::Char('k') | KeyCode::Up => Action::Decrement,
+      KeyCode::Home => Action::Top,
+      KeyCode::Char('r') | KeyCode::F(5) => Action::UpdateIncidents,
+      KeyCode::Char('o') | KeyCode::Enter => Action::Open,
+      KeyCode::Char(' ') => Action::ToggleMark,
+      KeyCode::Char('v') => Action::MarkRange,
+      KeyCode::Char('*') => Action::MarkVisible,
+      KeyCode::Char('a') => Action::Acknowledge,
+      KeyCode::Char('A') => Action::AcknowledgeAllService,
+      KeyCode::Char('x') => Action::Resolve,
+      KeyCode::Char('X') => Action::ResolveAllService,
+      KeyCode::Char('h') => Action::HideAck,
+      KeyCode::Char('g') => Action::AllIncidents,
+      KeyCode::Char('d') => Action::ShowDetails,
+      KeyCode::Char('n') => Action::ComposeNote,
+      KeyCode::Char('R') => Action::Reassign,
+      KeyCode::Char('e') => Action::Escalate,
+      KeyCode::Char('z') => Action::Snooze,
+      KeyCode::Char('m') => Action::Maintenance,
+      KeyCode::Char('/') => Action::Search,
+      KeyCode::Char('s') => Action::SortNext,
+      KeyCode::Char('S') => Action::ToggleSortOrder,
+      KeyCode::Esc if app.has_marks() => Action::ClearMarks,
+      KeyCode::Esc if app.service_filter.is_some() => Action::ClearServiceFilter,
+      KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+
+      _ => Action::None,
+    },
+    Mode::Detail => match key.code {
+      KeyCode::Char('j') | KeyCode::Down => Action::ScrollDetailsDown,
+      KeyCode::Char('k') | KeyCode::Up => Action::ScrollDetailsUp,
+      KeyCode::Char('o') | KeyCode::Enter => Action::Open,
+      KeyCode::Char('n') => Action::ComposeNote,
+      KeyCode::Char('R') => Action::Reassign,
+      KeyCode::Char('e') => Action::Escalate,
+      KeyCode::Char('d') | KeyCode::Char('q') | KeyCode::Esc => Action::CloseDetails,
+
+      _ => Action::None,
+    },
+    Mode::Note => match key.code {
+      KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::SubmitNote,
+      KeyCode::Char(c) => Action::InputChar(c),
+      KeyCode::Enter => Action::InputNewLine,
+      KeyCode::Backspace => Action::InputBackspace,
+      KeyCode::Esc => Action::CancelNote,
+
+      _ => Action::None,
+    },
+    Mode::Picker => match key.code {
+      KeyCode::Down => Action::PickerNext,
+      KeyCode::Up => Action::PickerPrevious,
+      KeyCode::Enter => Action::PickerSelect,
+      KeyCode::Char(c) => Action::InputChar(c),
+      KeyCode::Backspace => Action::InputBackspace,
+      KeyCode::Esc => Action::CancelPicker,
+
+      _ => Action::None,
+    },
+    Mode::Snooze => match key.code {
+      KeyCode::Down => Action::DurationNext,
+      KeyCode::Up => Action::DurationPrevious,
+      KeyCode::Enter => Action::SnoozeSelect,
+      KeyCode::Char(c) => Action::InputChar(c),
+      KeyCode::Backspace => Action::InputBackspace,
+      KeyCode::Esc => Action::CancelSnooze,
+
+      _ => Action::None,
+    },
+    Mode::Search => match key.code {
+      KeyCode::Down => Action::Increment,
+      KeyCode::Up => Action::Decrement,
+      KeyCode::Enter => Action::ApplySearch,
+      KeyCode::Char(c) => Action::InputChar(c),
+      KeyCode::Backspace => Action::InputBackspace,
+      KeyCode::Esc => Action::ClearSearch,
+
+      _ => Action::None,
+    },
+    Mode::Confirm => match key.code {
+      KeyCode::Char('y') | KeyCode::Enter => Action::ConfirmYes,
+      KeyCode::Char('n') | KeyCode::Esc => Action::ConfirmNo,
+
+      _ => Action::None,
+    },
+    Mode::OverrideForm => match key.code {
+      KeyCode::Tab | KeyCode::Down | KeyCode::Up => Action::NextOverrideField,
+      KeyCode::Enter => Action::SubmitOverride,
+      KeyCode::Char(c) => Action::InputChar(c),
+      KeyCode::Backspace => Action::InputBackspace,
+      KeyCode::Esc => Action::CancelOverride,
+
+      _ => Action::None,
+    },
+    Mode::Maintenance => match key.code {
+      KeyCode::Down => Action::DurationNext,
+      KeyCode::Up => Action::DurationPrevious,
+      KeyCode::Enter => Action::MaintenanceSelect,
+      KeyCode::Char(c) => Action::InputChar(c),
+      KeyCode::Backspace => Action::InputBackspace,
+      KeyCode::Esc => Action::CancelMaintenance,
+
+      _ => Action::None,
+    },
+    Mode::EndMaintenance => match key.code {
+      KeyCode::Char('j') | KeyCode::Down => Action::EndMaintenanceNext,
+      KeyCode::Char('k') | KeyCode::Up => Action::EndMaintenancePrevious,
+      KeyCode::Enter => Action::EndMaintenanceSelect,
+      KeyCode::Esc => Action::CancelMaintenance,
+
+      _ => Action::None,
+    },
+    Mode::DeleteOverride => match key.code {
+      KeyCode::Char('j') | KeyCode::Down => Action::OverrideNext,
+      KeyCode::Char('k') | KeyCode::Up => Action::OverridePrevious,
+      KeyCode::Enter => Action::OverrideSelect,
+      KeyCode::Esc => Action::CancelOverride,
+
+      _ => Action::None,
+    },
+  }
+}
+
+// Incidents of the service of the selected row
+fn service_incident_ids(app: &App, keep: impl Fn(&Incident) -> bool) -> Vec<String> {
+  let selected_service = app.selected_item().map(|item| item.service_id.clone()).unwrap_or_default();
+  app.items.iter()
+    .filter(|item| item.service_id == selected_service && keep(item))
+    .map(|item| item.id.clone())
+    .collect()
+}
+
+// Ask the user before sending the change when it is risky, send it straight away otherwise
+async fn confirm_or_run(app: &mut App, action: ConfirmAction, incident_ids: Vec<String>) {
+  let incident_change = !matches!(action, ConfirmAction::EndMaintenance(_) | ConfirmAction::DeleteOverride { .. });
+  if incident_change && incident_ids.is_empty() {
+    return;
+  }
+  if app.needs_confirmation(action.mutation(), incident_ids.len()) {
+    app.confirmation = Some(Confirmation { action, incident_ids });
+    app.mode = Mode::Confirm;
+  } else {
+    run_mutation(app, action, incident_ids).await;
+  }
+}
+
+async fn run_mutation(app: &mut App, action: ConfirmAction, incident_ids: Vec<String>) {
+  match action {
+    ConfirmAction::Acknowledge => acknowledge_all(app, incident_ids).await,
+    ConfirmAction::Resolve => resolve_all(app, incident_ids).await,
+    ConfirmAction::Escalate => {
+      for incident_id in &incident_ids {
+        escalate_async(&app.pager_duty, incident_id, app.outcomes_tx.clone()).await;
+        app.start_mutation(incident_id, Mutation::Escalate);
+      }
+    },
+    ConfirmAction::Snooze(duration) => {
+      // There is no bulk endpoint to snooze
+      for incident_id in &incident_ids {
+        snooze_async(&app.pager_duty, incident_id, duration, app.outcomes_tx.clone()).await;
+        app.start_mutation(incident_id, Mutation::Snooze);
+      }
+      app.clear_marks();
+    },
+    ConfirmAction::Reassign(assignee) => {
+      match incident_ids.as_slice() {
+        [incident_id] => reassign_async(&app.pager_duty, incident_id, &assignee, app.outcomes_tx.clone()).await,
+        _ => bulk_reassign_async(&app.pager_duty, incident_ids.clone(), &assignee, app.outcomes_tx.clone()).await,
+      };
+      for incident_id in &incident_ids {
+        app.start_mutation(incident_id, Mutation::Reassign);
+      }
+      app.clear_marks();
+    },
+    ConfirmAction::Note(content) => {
+      if let [incident_id] = incident_ids.as_slice() {
+        // Show the incident with its new note
+        app.mode = Mode::Detail;
+        app.details = None;
+        app.details_id = Some(incident_id.clone());
+        app.details_scroll = 0;
+        add_note_async(&app.pager_duty, incident_id, &content, Some(app.details_tx.clone()), app.outcomes_tx.clone()).await;
+      } else {
+        for incident_id in &incident_ids {
+          add_note_async(&app.pager_duty, incident_id, &content, None, app.outcomes_tx.clone()).await;
+          app.start_mutation(incident_id, Mutation::Note);
+        }
+        app.clear_marks();
+      }
+    },
+    ConfirmAction::EndMaintenance(window_id) => {
+      end_maintenance_window_async(&app.pager_duty, &window_id, app.maintenance_outcomes_tx.clone()).await;
+    },
+    ConfirmAction::DeleteOverride { schedule_id, override_id } => {
+      delete_override_async(&app.pager_duty, &schedule_id, &override_id, app.override_outcomes_tx.clone()).await;
+    },
+  }
+}
+
+// One incident is updated on its own, several at once with the bulk endpoint
+async fn acknowledge_all(app: &mut App, incident_ids: Vec<String>) {
+  match incident_ids.as_slice() {
+    [] => {},
+    [incident_id] => acknowledge_async(&app.pager_duty, incident_id, app.outcomes_tx.clone()).await,
+    _ => bulk_acknowledge_async(&app.pager_duty, incident_ids.clone(), app.outcomes_tx.clone()).await,
+  }
+  for incident_id in &incident_ids {
+    app.start_mutation(incident_id, Mutation::Acknowledge);
+  }
+  app.clear_marks();
+}
+
+async fn resolve_all(app: &mut App, incident_ids: Vec<String>) {
+  match incident_ids.as_slice() {
+    [] => {},
+    [incident_id] => resolve_async(&app.pager_duty, incident_id, app.outcomes_tx.clone()).await,
+    _ => bulk_resolve_async(&app.pager_duty, incident_ids.clone(), app.outcomes_tx.clone()).await,
+  }
+  for incident_id in &incident_ids {
+    app.start_mutation(incident_id, Mutation::Resolve);
+  }
+  app.clear_marks();
+}
+
+// Clicks on the header of the table sort by the column
+fn handle_mouse(app: &App, mouse: MouseEvent) -> Action {
+  if app.mode != Mode::Normal || app.tab != Tab::Incidents || mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+    return Action::None;
+  }
+  let position = ratatui::layout::Position { x: mouse.column, y: mouse.row };
+  app.header_columns.iter()
+    .find(|(area, _)| area.contains(position))
+    .map_or(Action::None, |&(_, column)| Action::SortBy(column))
+}
+
 pub fn handle_event(_app: &App, tx: mpsc::UnboundedSender<Action>) -> tokio::task::JoinHandle<()> {
   let tick_rate = std::time::Duration::from_millis(u64::try_from(REFRESH_RATE).expect("Refresh rate not valid"));
 
   tokio::spawn(async move {
     loop {
       let action = if crossterm::event::poll(tick_rate).unwrap() {
-        if let crossterm::event::Event::Key(key) = crossterm::event::read().unwrap() {
-          if key.kind == crossterm::event::KeyEventKind::Press {
-            match key.code {
-              crossterm::event::KeyCode::Char('j') | crossterm::event::KeyCode::Down => Action::Increment,
-              crossterm::event::KeyCode::Char('k') | crossterm::event::KeyCode::Up => Action::Decrement,
-              crossterm::event::KeyCode::Home => Action::Top,
-              crossterm::event::KeyCode::Char('r') | crossterm::event::KeyCode::F(5) => Action::UpdateIncidents,
-              crossterm::event::KeyCode::Char('o') | crossterm::event::KeyCode::Enter => Action::Open,
-              crossterm::event::KeyCode::Char(' ') => Action::Acknowledge,
-              crossterm::event::KeyCode::Char('a') | crossterm::event::KeyCode::Char('A') => Action::AcknowledgeAllService,
-              crossterm::event::KeyCode::Char('h') => Action::HideAck,
-              crossterm::event::KeyCode::Char('g') => Action::AllIncidents,
-              crossterm::event::KeyCode::Char('q') | crossterm::event::KeyCode::Esc => Action::Quit,
-
-              _ => Action::None,
-            }
-          } else {
-            Action::None
-          }
-        } else {
-          Action::None
+        match crossterm::event::read().unwrap() {
+          crossterm::event::Event::Key(key) if key.kind == crossterm::event::KeyEventKind::Press => Action::Key(key),
+          crossterm::event::Event::Mouse(mouse) => Action::Mouse(mouse),
+          _ => Action::None,
         }
       } else {
         Action::None
       };
-      if let Err(_) = tx.send(action) {
+      if tx.send(action).is_err() {
         break;
       }
     }
@@ -2,18 +2,39 @@ use ratatui::{
   prelude::*, 
   widgets::*,
 };
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{self, UnboundedSender,UnboundedReceiver};
-use unicode_width::UnicodeWidthStr;
 use style::palette::tailwind;
 
-use crate::config::AppConfig;
-use crate::pagerduty::{Incident, PagerDuty, get_items_async};
+use crate::config::{AppConfig, AppState};
+use crate::filter::Filter;
+use crate::notify::{newly_triggered, notify};
+use crate::sort::SortColumn;
+use crate::utils::TimeFormat;
+use crate::pagerduty::{Assignee, AssigneeKind, Incident, DetailsResponse, IncidentDetails, IncidentStatus, ItemsResponse, MaintenanceOutcome, MaintenanceWindow, Mutation, MutationOutcome, OnCall, OverrideOutcome, PagerDuty, PagerDutyError, RetryNotice, Schedule, ScheduleEntry, Service, ScheduleTimeline, get_items_async, get_maintenance_windows_async, get_schedule_timeline_async, get_services_async};
 use crate::actions::{Action,handle_event,update,REFRESH_RATE};
 use crate::ui::{ui,splash_screen};
 
 const ITEM_HEIGHT: usize = 4;
 
+// Days of upcoming shifts shown in the on-call tab
+pub const ONCALL_DAYS: i64 = 7;
+
+// Days shown in the timeline of the schedules tab, switched with W
+pub const SCHEDULE_DAYS: [i64; 2] = [7, 14];
+
+// How long new and changed rows stay highlighted after a refresh
+const HIGHLIGHT_DURATION: Duration = Duration::from_secs(5);
+
+// Durations offered when snoozing or starting a maintenance, the last choice of the prompt is a custom one
+pub const DURATIONS: [(&str, u64); 3] = [
+  ("30 minutes", 1800),
+  ("1 hour", 3600),
+  ("4 hours", 14400),
+];
+
 const PALETTES: [tailwind::Palette; 1] = [
   tailwind::GREEN,
 ];
@@ -29,6 +50,11 @@ pub struct TableColors {
   pub footer_border_color: Color,
   pub triggered_normal_color: Color,
   pub triggered_alt_color: Color,
+  pub triggered_low_normal_color: Color,
+  pub triggered_low_alt_color: Color,
+  pub highlight_color: Color,
+  pub marked_color: Color,
+  pub override_color: Color,
 }
 
 impl TableColors {
@@ -44,25 +70,243 @@ impl TableColors {
       footer_border_color: color.c400,
       triggered_normal_color: tailwind::RED.c700,
       triggered_alt_color: tailwind::RED.c500,
+      triggered_low_normal_color: tailwind::AMBER.c800,
+      triggered_low_alt_color: tailwind::AMBER.c700,
+      highlight_color: tailwind::EMERALD.c700,
+      marked_color: tailwind::BLUE.c700,
+      override_color: tailwind::AMBER.c500,
+    }
+  }
+}
+
+// Screens of the app, switched with Tab
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tab {
+  Incidents,
+  Services,
+  OnCall,
+  Schedules,
+}
+
+impl Tab {
+  pub const ALL: [Tab; 4] = [Tab::Incidents, Tab::Services, Tab::OnCall, Tab::Schedules];
+
+  pub fn title(&self) -> &'static str {
+    match self {
+      Tab::Incidents => "Incidents",
+      Tab::Services => "Services",
+      Tab::OnCall => "On call",
+      Tab::Schedules => "Schedules",
+    }
+  }
+
+  pub fn next(&self) -> Tab {
+    let i = Tab::ALL.iter().position(|tab| tab == self).unwrap_or(0);
+    Tab::ALL[(i + 1) % Tab::ALL.len()]
+  }
+}
+
+#[derive(PartialEq)]
+pub enum Mode {
+  Normal,
+  Detail,
+  Note,
+  Picker,
+  Snooze,
+  Search,
+  Confirm,
+  OverrideForm,
+  DeleteOverride,
+  Maintenance,
+  EndMaintenance,
+}
+
+// Change waiting for the user to confirm it, with what it needs to be sent
+pub enum ConfirmAction {
+  Acknowledge,
+  Resolve,
+  Escalate,
+  Snooze(u64),
+  Reassign(Assignee),
+  Note(String),
+  EndMaintenance(String), // maintenance window id
+  DeleteOverride { schedule_id: String, override_id: String },
+}
+
+impl ConfirmAction {
+  pub fn mutation(&self) -> Mutation {
+    match self {
+      ConfirmAction::Acknowledge => Mutation::Acknowledge,
+      ConfirmAction::Resolve => Mutation::Resolve,
+      ConfirmAction::Escalate => Mutation::Escalate,
+      ConfirmAction::Snooze(_) => Mutation::Snooze,
+      ConfirmAction::Reassign(_) => Mutation::Reassign,
+      ConfirmAction::Note(_) => Mutation::Note,
+      ConfirmAction::EndMaintenance(_) => Mutation::EndMaintenance,
+      ConfirmAction::DeleteOverride { .. } => Mutation::DeleteOverride,
+    }
+  }
+}
+
+pub struct Confirmation {
+  pub action: ConfirmAction,
+  pub incident_ids: Vec<String>,
+}
+
+// What the user picked in the picker is for
+#[derive(Clone, Copy, PartialEq)]
+pub enum PickerPurpose {
+  Reassign,
+  Override, // only users can be on call in an override
+}
+
+// Searchable list of users and escalation policies
+pub struct Picker {
+  pub title: String,
+  pub purpose: PickerPurpose,
+  pub entries: Option<Result<Vec<Assignee>,PagerDutyError>>,
+  pub state: ListState,
+}
+
+impl Picker {
+  pub fn new(title: &str) -> Self {
+    Self {
+      title: String::from(title),
+      purpose: PickerPurpose::Reassign,
+      entries: None,
+      state: ListState::default().with_selected(Some(0)),
+    }
+  }
+
+  pub fn filtered(&self, filter: &str) -> Vec<&Assignee> {
+    let filter = filter.to_lowercase();
+    match &self.entries {
+      Some(Ok(entries)) => entries.iter()
+        .filter(|entry| self.purpose == PickerPurpose::Reassign || entry.kind == AssigneeKind::User)
+        .filter(|entry| entry.name.to_lowercase().contains(&filter))
+        .collect(),
+      _ => Vec::new(),
     }
   }
+
+  pub fn selected<'a>(&'a self, filter: &str) -> Option<&'a Assignee> {
+    self.filtered(filter).get(self.state.selected()?).copied()
+  }
+
+  pub fn next(&mut self, filter: &str) {
+    let len = self.filtered(filter).len();
+    let i = self.state.selected().map_or(0, |i| if i + 1 >= len { 0 } else { i + 1 });
+    self.state.select(Some(i));
+  }
+
+  pub fn previous(&mut self, filter: &str) {
+    let len = self.filtered(filter).len();
+    let i = self.state.selected().map_or(0, |i| if i == 0 { len.saturating_sub(1) } else { i - 1 });
+    self.state.select(Some(i));
+  }
+}
+
+// Override being typed, for the user picked before
+pub struct OverrideForm {
+  pub schedule_id: String,
+  pub user: Assignee,
+  pub start: String,
+  pub end: String,
+  pub editing_end: bool,
+  pub error: Option<String>,
+}
+
+impl OverrideForm {
+  pub fn field_mut(&mut self) -> &mut String {
+    if self.editing_end { &mut self.end } else { &mut self.start }
+  }
+}
+
+// Row of an incident as it was before a mutation was sent, to put it back if the mutation fails
+pub struct PendingMutation {
+  pub incident_id: String,
+  pub mutation: Mutation,
+  pub status: IncidentStatus,
 }
 
 pub struct App {
   pub state: TableState,
   pub items: Vec<Incident>,
+  pub visible: Vec<usize>, // indexes in items of the rows shown, the selection is a position in it
+  pub filter: String,
+  pub service_filter: Option<(String, String)>, // id and name of the service whose incidents are shown
+  pub sort_by: Option<SortColumn>, // None keeps the order of PagerDuty
+  pub sort_descending: bool,
+  pub time_format: TimeFormat,
+  pub header_columns: Vec<(Rect, SortColumn)>, // where the sortable headers were drawn, for clicks
+  pub config: AppConfig,
   pub pager_duty: PagerDuty,
-  pub longest_item_lens: (u16, u16, u16), // order is (status,summary,created_at)
   pub scroll_state: ScrollbarState,
   pub colors: TableColors,
   pub color_index: usize,
   pub action_tx: UnboundedSender<Action>,
   pub action_rx: UnboundedReceiver<Action>,
-  pub items_tx: UnboundedSender<Vec<Incident>>,
-  pub items_rx: UnboundedReceiver<Vec<Incident>>,
+  pub items_tx: UnboundedSender<ItemsResponse>,
+  pub items_rx: UnboundedReceiver<ItemsResponse>,
+  pub outcomes_tx: UnboundedSender<MutationOutcome>,
+  pub outcomes_rx: UnboundedReceiver<MutationOutcome>,
+  pub pending: Vec<PendingMutation>,
+  pub progress: HashMap<String, String>, // last mutation sent from the TUI per incident, shown until the next refresh
+  pub error: Option<String>,
+  pub retry_rx: UnboundedReceiver<RetryNotice>,
+  pub retry: Option<(RetryNotice, Instant)>,
+  pub mode: Mode,
+  pub details: Option<Result<IncidentDetails,PagerDutyError>>,
+  pub details_scroll: u16,
+  pub details_id: Option<String>, // incident open in the detail pane, other details received are dropped
+  pub details_tx: UnboundedSender<DetailsResponse>,
+  pub details_rx: UnboundedReceiver<DetailsResponse>,
+  pub input: String,
+  pub target_incident_ids: Vec<String>, // incidents the open note composer, picker or snooze prompt apply to
+  pub marked: HashSet<String>, // incidents picked for a bulk action
+  pub mark_anchor: Option<String>, // incident where the range being marked starts
+  pub confirmation: Option<Confirmation>,
+  pub tab: Tab,
+  pub oncalls: Option<Result<Vec<OnCall>,PagerDutyError>>,
+  pub oncalls_scroll: u16,
+  pub all_oncalls: bool,
+  pub oncalls_tx: UnboundedSender<Result<Vec<OnCall>,PagerDutyError>>,
+  pub oncalls_rx: UnboundedReceiver<Result<Vec<OnCall>,PagerDutyError>>,
+  pub services: Option<Result<Vec<Service>,PagerDutyError>>,
+  pub services_state: TableState,
+  pub services_scroll_state: ScrollbarState,
+  pub services_tx: UnboundedSender<Result<Vec<Service>,PagerDutyError>>,
+  pub services_rx: UnboundedReceiver<Result<Vec<Service>,PagerDutyError>>,
+  pub maintenance_windows: Option<Result<Vec<MaintenanceWindow>,PagerDutyError>>,
+  pub maintenance_windows_tx: UnboundedSender<Result<Vec<MaintenanceWindow>,PagerDutyError>>,
+  pub maintenance_windows_rx: UnboundedReceiver<Result<Vec<MaintenanceWindow>,PagerDutyError>>,
+  pub maintenance_service: Option<(String, String)>, // id and name of the service the duration prompt is for
+  pub maintenance_state: ListState, // window picked to be ended
+  pub maintenance_outcomes_tx: UnboundedSender<MaintenanceOutcome>,
+  pub maintenance_outcomes_rx: UnboundedReceiver<MaintenanceOutcome>,
+  pub schedules: Option<Result<Vec<Schedule>,PagerDutyError>>,
+  pub schedules_state: TableState,
+  pub schedules_scroll_state: ScrollbarState,
+  pub schedules_tx: UnboundedSender<Result<Vec<Schedule>,PagerDutyError>>,
+  pub schedules_rx: UnboundedReceiver<Result<Vec<Schedule>,PagerDutyError>>,
+  pub timeline: Option<Result<ScheduleTimeline,PagerDutyError>>, // shifts of the selected schedule
+  pub timeline_days: i64,
+  pub timeline_tx: UnboundedSender<Result<ScheduleTimeline,PagerDutyError>>,
+  pub timeline_rx: UnboundedReceiver<Result<ScheduleTimeline,PagerDutyError>>,
+  pub override_form: Option<OverrideForm>,
+  pub override_state: ListState, // override of the user picked to be deleted
+  pub override_outcomes_tx: UnboundedSender<OverrideOutcome>,
+  pub override_outcomes_rx: UnboundedReceiver<OverrideOutcome>,
+  pub picker: Picker,
+  pub duration_state: ListState,
+  pub assignees_tx: UnboundedSender<Result<Vec<Assignee>,PagerDutyError>>,
+  pub assignees_rx: UnboundedReceiver<Result<Vec<Assignee>,PagerDutyError>>,
   pub refreshing: bool,
   pub hide_ack: bool,
   pub all_incidents: bool,
+  pub scope_changed: bool, // the next list is not comparable to the current one
+  pub highlights: HashMap<String, Instant>, // new and changed incidents, with when they were received
+  pub unseen: HashSet<String>, // new incidents not selected yet
   pub should_quit: bool,
   pub refresh_rate: Option<i64>,
   pub ticker: i64,
@@ -70,35 +314,118 @@ pub struct App {
 
 impl App {
   pub async fn new(pd: PagerDuty, config: &AppConfig) -> Self {
+    let (retry_tx, retry_rx) = mpsc::unbounded_channel();
+    pd.set_retry_tx(retry_tx);
 
-    let data_vec = pd.get_incidents(false).await.expect("Error getting incidents from PagerDuty");
+    let (data_vec, error) = match pd.get_incidents(false).await {
+      Ok(data_vec) => (data_vec, None),
+      Err(err) => (Vec::new(), Some(err.to_string())),
+    };
+    // Without a first list, every incident of the first refresh would look new
+    let first_load_failed = error.is_some();
     let (action_tx, action_rx) = mpsc::unbounded_channel();
     let (items_tx, items_rx) = mpsc::unbounded_channel();
+    let (outcomes_tx, outcomes_rx) = mpsc::unbounded_channel();
+    let (details_tx, details_rx) = mpsc::unbounded_channel();
+    let (assignees_tx, assignees_rx) = mpsc::unbounded_channel();
+    let (oncalls_tx, oncalls_rx) = mpsc::unbounded_channel();
+    let (services_tx, services_rx) = mpsc::unbounded_channel();
+    let (maintenance_windows_tx, maintenance_windows_rx) = mpsc::unbounded_channel();
+    let (maintenance_outcomes_tx, maintenance_outcomes_rx) = mpsc::unbounded_channel();
+    let (schedules_tx, schedules_rx) = mpsc::unbounded_channel();
+    let (timeline_tx, timeline_rx) = mpsc::unbounded_channel();
+    let (override_outcomes_tx, override_outcomes_rx) = mpsc::unbounded_channel();
+    let (sort_by, sort_descending) = AppState::load().map_or_else(|| config.get_sort(), |state| state.get_sort());
 
-    Self {
+    let mut app = Self {
       state: TableState::default().with_selected(0),
-      longest_item_lens: constraint_len_calculator(&data_vec),
-      scroll_state: ScrollbarState::new((data_vec.len() - 1) * ITEM_HEIGHT),
+      scroll_state: ScrollbarState::new(data_vec.len().saturating_sub(1) * ITEM_HEIGHT),
       colors: TableColors::new(&PALETTES[0]),
       color_index: 0,
+      visible: (0..data_vec.len()).collect(),
+      filter: String::new(),
+      service_filter: None,
+      sort_by,
+      sort_descending,
+      time_format: config.get_time_format(),
+      header_columns: Vec::new(),
+      config: config.clone(),
       items: data_vec,
       pager_duty: pd,
       should_quit: false,
       refreshing: false,
       hide_ack: false,
       all_incidents: false,
+      scope_changed: first_load_failed,
+      highlights: HashMap::new(),
+      unseen: HashSet::new(),
       action_tx,
       action_rx,
       items_tx,
       items_rx,
+      outcomes_tx,
+      outcomes_rx,
+      pending: Vec::new(),
+      progress: HashMap::new(),
+      error,
+      retry_rx,
+      retry: None,
+      mode: Mode::Normal,
+      details: None,
+      details_scroll: 0,
+      details_id: None,
+      details_tx,
+      details_rx,
+      input: String::new(),
+      target_incident_ids: Vec::new(),
+      marked: HashSet::new(),
+      mark_anchor: None,
+      confirmation: None,
+      tab: Tab::Incidents,
+      oncalls: None,
+      oncalls_scroll: 0,
+      all_oncalls: false,
+      oncalls_tx,
+      oncalls_rx,
+      services: None,
+      services_state: TableState::default().with_selected(0),
+      services_scroll_state: ScrollbarState::new(0),
+      services_tx,
+      services_rx,
+      maintenance_windows: None,
+      maintenance_windows_tx,
+      maintenance_windows_rx,
+      maintenance_service: None,
+      maintenance_state: ListState::default().with_selected(Some(0)),
+      maintenance_outcomes_tx,
+      maintenance_outcomes_rx,
+      schedules: None,
+      schedules_state: TableState::default().with_selected(0),
+      schedules_scroll_state: ScrollbarState::new(0),
+      schedules_tx,
+      schedules_rx,
+      timeline: None,
+      timeline_days: SCHEDULE_DAYS[0],
+      timeline_tx,
+      timeline_rx,
+      override_form: None,
+      override_state: ListState::default().with_selected(Some(0)),
+      override_outcomes_tx,
+      override_outcomes_rx,
+      picker: Picker::new(""),
+      duration_state: ListState::default().with_selected(Some(0)),
+      assignees_tx,
+      assignees_rx,
       refresh_rate: *config.get_refresh_rate(),
       ticker: 0,
-    }
+    };
+    app.update_view();
+    app
   }
   pub fn next(&mut self) {
     let i = match self.state.selected() {
       Some(i) => {
-        if i >= self.items.len() - 1 {
+        if i + 1 >= self.visible.len() {
           0
         } else {
           i + 1
@@ -114,7 +441,7 @@ impl App {
     let i = match self.state.selected() {
       Some(i) => {
         if i == 0 {
-          self.items.len() - 1
+          self.visible.len().saturating_sub(1)
         } else {
           i - 1
         }
@@ -125,8 +452,346 @@ impl App {
     self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
   }
 
+  // Index in items of the selected row
+  pub fn selected_index(&self) -> Option<usize> {
+    self.visible.get(self.state.selected()?).copied()
+  }
+
+  pub fn selected_item(&self) -> Option<&Incident> {
+    self.items.get(self.selected_index()?)
+  }
+
+  pub fn selected_service(&self) -> Option<&Service> {
+    let services = self.services.as_ref()?.as_ref().ok()?;
+    services.get(self.services_state.selected()?)
+  }
+
+  pub fn next_service(&mut self) {
+    let len = self.services.as_ref().and_then(|res| res.as_ref().ok()).map_or(0, |services| services.len());
+    let i = self.services_state.selected().map_or(0, |i| (i + 1).min(len.saturating_sub(1)));
+    self.services_state.select(Some(i));
+    self.services_scroll_state = self.services_scroll_state.position(i);
+  }
+
+  pub fn previous_service(&mut self) {
+    let i = self.services_state.selected().map_or(0, |i| i.saturating_sub(1));
+    self.services_state.select(Some(i));
+    self.services_scroll_state = self.services_scroll_state.position(i);
+  }
+
+  // Show a new list of services, staying on the same service when it is still there
+  pub fn replace_services(&mut self, services: Result<Vec<Service>,PagerDutyError>) {
+    let selected_id = self.selected_service().map(|service| service.id.clone());
+    let i = match (&services, &selected_id) {
+      (Ok(services), Some(id)) => services.iter().position(|service| &service.id == id).unwrap_or(0),
+      _ => 0,
+    };
+    let len = services.as_ref().map_or(0, |services| services.len());
+    self.services = Some(services);
+    self.services_state.select(Some(i));
+    self.services_scroll_state = self.services_scroll_state.content_length(len).position(i);
+  }
+
+  // Services and their maintenance windows are loaded together, a window changes the status of its services
+  pub async fn load_services(&mut self) {
+    get_services_async(&self.pager_duty, self.services_tx.clone()).await;
+    get_maintenance_windows_async(&self.pager_duty, self.maintenance_windows_tx.clone()).await;
+  }
+
+  pub fn maintenance_windows(&self) -> &[MaintenanceWindow] {
+    match &self.maintenance_windows {
+      Some(Ok(windows)) => windows,
+      _ => &[],
+    }
+  }
+
+  pub fn selected_schedule(&self) -> Option<&Schedule> {
+    let schedules = self.schedules.as_ref()?.as_ref().ok()?;
+    schedules.get(self.schedules_state.selected()?)
+  }
+
+  pub async fn next_schedule(&mut self) {
+    let len = self.schedules.as_ref().and_then(|res| res.as_ref().ok()).map_or(0, |schedules| schedules.len());
+    if len == 0 {
+      return;
+    }
+    let i = self.schedules_state.selected().map_or(0, |i| (i + 1).min(len - 1));
+    self.select_schedule(i).await;
+  }
+
+  pub async fn previous_schedule(&mut self) {
+    let i = self.schedules_state.selected().map_or(0, |i| i.saturating_sub(1));
+    self.select_schedule(i).await;
+  }
+
+  async fn select_schedule(&mut self, i: usize) {
+    if self.schedules_state.selected() == Some(i) {
+      return;
+    }
+    self.schedules_state.select(Some(i));
+    self.schedules_scroll_state = self.schedules_scroll_state.position(i);
+    self.load_timeline().await;
+  }
+
+  // Ask PagerDuty for the shifts of the selected schedule, the previous ones are dropped
+  pub async fn load_timeline(&mut self) {
+    self.timeline = None;
+    if let Some(id) = self.selected_schedule().map(|schedule| schedule.id.clone()) {
+      get_schedule_timeline_async(&self.pager_duty, &id, self.timeline_days, self.timeline_tx.clone()).await;
+    }
+  }
+
+  // Overrides of the selected schedule the current user can delete
+  pub fn own_overrides(&self) -> Vec<&ScheduleEntry> {
+    let current_user_id = self.pager_duty.current_user_id();
+    match &self.timeline {
+      Some(Ok(timeline)) => timeline.overrides.iter()
+        .filter(|schedule_override| schedule_override.user_id == current_user_id)
+        .collect(),
+      _ => Vec::new(),
+    }
+  }
+
+  // Show a new list of schedules, staying on the same schedule when it is still there
+  pub async fn replace_schedules(&mut self, schedules: Result<Vec<Schedule>,PagerDutyError>) {
+    let selected_id = self.selected_schedule().map(|schedule| schedule.id.clone());
+    let i = match (&schedules, &selected_id) {
+      (Ok(schedules), Some(id)) => schedules.iter().position(|schedule| &schedule.id == id).unwrap_or(0),
+      _ => 0,
+    };
+    let len = schedules.as_ref().map_or(0, |schedules| schedules.len());
+    self.schedules = Some(schedules);
+    self.schedules_state.select(Some(i));
+    self.schedules_scroll_state = self.schedules_scroll_state.content_length(len).position(i);
+    self.load_timeline().await;
+  }
+
+  // Work out the rows to show after the items, the filter or the hidden acks changed,
+  // staying on the same incident when it is still shown
+  pub fn update_view(&mut self) {
+    let selected_id = self.selected_item().map(|item| item.id.clone());
+    let filter = Filter::parse(&self.filter);
+
+    self.visible = self.items.iter()
+      .enumerate()
+      .filter(|(_, item)| !self.hide_ack || item.triggered())
+      .filter(|(_, item)| filter.matches(item))
+      .filter(|(_, item)| self.service_filter.as_ref().is_none_or(|(service_id, _)| &item.service_id == service_id))
+      .map(|(i, _)| i)
+      .collect();
+    if let Some(column) = self.sort_by {
+      let items = &self.items;
+      self.visible.sort_by(|&a, &b| column.compare(&items[a], &items[b], self.sort_descending));
+    }
+
+    let i = selected_id
+      .and_then(|id| self.visible.iter().position(|&i| self.items[i].id == id))
+      .unwrap_or(0);
+    self.state.select(Some(i));
+    self.scroll_state = self.scroll_state
+      .content_length(self.visible.len().saturating_sub(1) * ITEM_HEIGHT)
+      .position(i * ITEM_HEIGHT);
+  }
+
+  // Sort by the column, or switch the order when already sorted by it, and save the choice
+  pub fn sort(&mut self, sort_by: Option<SortColumn>, sort_descending: bool) {
+    self.sort_by = sort_by;
+    self.sort_descending = sort_descending;
+    self.update_view();
+    if let Err(err) = AppState::new(sort_by, sort_descending).save() {
+      self.error = Some(format!("Could not save the sort order: {}", err));
+    }
+  }
+
+  // Take the new list from PagerDuty, telling what is new or changed since the last one
+  pub fn replace_items(&mut self, items: Vec<Incident>) {
+    if self.scope_changed {
+      self.highlights.clear();
+      self.unseen.clear();
+    } else {
+      notify(self.config.get_notifications(), &newly_triggered(&self.items, &items));
+
+      let old: HashMap<&str, &Incident> = self.items.iter().map(|item| (item.id(), item)).collect();
+      let now = Instant::now();
+      for item in &items {
+        match old.get(item.id()) {
+          None => {
+            self.highlights.insert(item.id.clone(), now);
+            self.unseen.insert(item.id.clone());
+          }
+          Some(old_item) if *old_item != item => {
+            self.highlights.insert(item.id.clone(), now);
+          }
+          _ => {}
+        }
+      }
+    }
+    self.scope_changed = false;
+
+    self.items = items;
+    let ids: HashSet<&str> = self.items.iter().map(Incident::id).collect();
+    self.highlights.retain(|id, received_at| ids.contains(id.as_str()) && received_at.elapsed() < HIGHLIGHT_DURATION);
+    self.unseen.retain(|id| ids.contains(id.as_str()));
+    self.marked.retain(|id| ids.contains(id.as_str()));
+    if self.mark_anchor.as_ref().is_some_and(|id| !ids.contains(id.as_str())) {
+      self.mark_anchor = None;
+    }
+    self.progress.clear();
+    self.update_view();
+  }
+
+  pub fn is_highlighted(&self, id: &str) -> bool {
+    self.highlights.get(id).is_some_and(|received_at| received_at.elapsed() < HIGHLIGHT_DURATION)
+  }
+
+  // Rows between where the range started and the selection
+  fn range_ids(&self) -> HashSet<&str> {
+    let anchor = self.mark_anchor.as_ref()
+      .and_then(|id| self.visible.iter().position(|&i| &self.items[i].id == id));
+    match (anchor, self.state.selected()) {
+      (Some(anchor), Some(selected)) => self.visible[anchor.min(selected)..=anchor.max(selected).min(self.visible.len().saturating_sub(1))]
+        .iter()
+        .map(|&i| self.items[i].id())
+        .collect(),
+      _ => HashSet::new(),
+    }
+  }
+
+  // Marked rows and the open range, worked out once rather than per row
+  pub fn marked_ids(&self) -> HashSet<&str> {
+    let mut ids = self.range_ids();
+    ids.extend(self.marked.iter().map(String::as_str));
+    ids
+  }
+
+  pub fn has_marks(&self) -> bool {
+    !self.marked.is_empty() || self.mark_anchor.is_some()
+  }
+
+  pub fn toggle_mark(&mut self) {
+    if let Some(id) = self.selected_item().map(|item| item.id.clone()) {
+      if !self.marked.remove(&id) {
+        self.marked.insert(id);
+      }
+    }
+  }
+
+  // Start a range on the selection, or keep the range marked when one was started
+  pub fn mark_range(&mut self) {
+    if self.mark_anchor.is_some() {
+      let range: Vec<String> = self.range_ids().into_iter().map(String::from).collect();
+      self.marked.extend(range);
+      self.mark_anchor = None;
+    } else {
+      self.mark_anchor = self.selected_item().map(|item| item.id.clone());
+    }
+  }
+
+  // Every row left by the filter and the hidden acks
+  pub fn mark_visible(&mut self) {
+    let ids: Vec<String> = self.visible.iter().map(|&i| self.items[i].id.clone()).collect();
+    self.marked.extend(ids);
+  }
+
+  pub fn clear_marks(&mut self) {
+    self.marked.clear();
+    self.mark_anchor = None;
+  }
+
+  // Incidents an action applies to: the marked ones in the list, or the one the user is working on
+  pub fn target_ids(&self) -> Vec<String> {
+    if self.mode == Mode::Normal && self.has_marks() {
+      let marked = self.marked_ids();
+      self.items.iter()
+        .filter(|item| marked.contains(item.id.as_str()))
+        .map(|item| item.id.clone())
+        .collect()
+    } else {
+      self.current_incident_id().into_iter().collect()
+    }
+  }
+
+  // Target incidents, leaving out the listed ones the action does not apply to
+  pub fn target_ids_where(&self, keep: impl Fn(&Incident) -> bool) -> Vec<String> {
+    self.target_ids().into_iter()
+      .filter(|id| self.items.iter().find(|item| &item.id == id).is_none_or(&keep))
+      .collect()
+  }
+
+  // Resolving, escalating, ending a maintenance window and deleting an override cannot be undone,
+  // and changing several incidents at once is easy to do by mistake
+  pub fn needs_confirmation(&self, mutation: Mutation, count: usize) -> bool {
+    if self.config.get_skip_confirmation().contains(&mutation) {
+      return false;
+    }
+    matches!(mutation, Mutation::Resolve | Mutation::Escalate | Mutation::EndMaintenance | Mutation::DeleteOverride) || count > 1
+  }
+
+  // Incident the user is working on: the one in the detail pane, or the selected row
+  pub fn current_incident_id(&self) -> Option<String> {
+    match &self.details_id {
+      Some(details_id) if self.mode != Mode::Normal => Some(details_id.clone()),
+      _ => self.selected_item().map(|item| item.id.clone()),
+    }
+  }
+
+  // Show on the row of the incident that a mutation was sent, keeping the row as it was in case it fails
+  pub fn start_mutation(&mut self, incident_id: &str, mutation: Mutation) {
+    if let Some(item) = self.items.iter_mut().find(|item| item.id == incident_id) {
+      self.pending.push(PendingMutation {
+        incident_id: String::from(incident_id),
+        mutation,
+        status: item.status,
+      });
+      self.progress.insert(String::from(incident_id), format!("Sending {}", mutation.label()));
+      match mutation {
+        Mutation::Acknowledge => item.status = IncidentStatus::Acknowledged,
+        Mutation::Resolve => item.status = IncidentStatus::Resolved,
+        _ => {}
+      }
+    }
+    self.update_view();
+  }
+
+  // Confirm the mutation on the row, or put the row back and show the error
+  pub fn finish_mutation(&mut self, outcome: MutationOutcome) {
+    let pending = self.pending.iter()
+      .position(|pending| pending.incident_id == outcome.incident_id && pending.mutation == outcome.mutation)
+      .map(|i| self.pending.remove(i));
+    let item = self.items.iter_mut().find(|item| item.id == outcome.incident_id);
+
+    match outcome.result {
+      Ok(()) => {
+        if item.is_some() {
+          self.progress.insert(outcome.incident_id.clone(), format!("{} confirmed", outcome.mutation.label()));
+        }
+      }
+      Err(err) => {
+        if let (Some(item), Some(pending)) = (item, pending) {
+          item.status = pending.status;
+        }
+        self.progress.remove(&outcome.incident_id);
+        // A bulk action lists every incident that failed
+        let failed = format!("{} failed for", outcome.mutation.label());
+        self.error = Some(match self.error.take() {
+          Some(error) if error.starts_with(&failed) => format!("{}, {}: {}", error, outcome.incident_id, err),
+          _ => format!("{} {}: {}", failed, outcome.incident_id, err),
+        });
+      }
+    }
+    self.update_view();
+  }
+
+  // Seconds before a request is sent again, while PagerDuty is rate limiting or failing
+  pub fn retry_in(&self) -> Option<(&RetryNotice, u64)> {
+    let (retry, received_at) = self.retry.as_ref()?;
+    let remaining = retry.delay.checked_sub(received_at.elapsed())?;
+    Some((retry, remaining.as_secs() + 1))
+  }
+
   pub fn top(&mut self) {
     self.state.select(Some(0));
+    self.scroll_state = self.scroll_state.position(0);
   }
 
   pub fn set_colors(&mut self) {
@@ -136,27 +801,105 @@ impl App {
 
 pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
 
-  let _ = terminal.draw(|f,|splash_screen(f));
+  let _ = terminal.draw(splash_screen);
 
   let _task = handle_event(&app, app.action_tx.clone());
 
   loop {
     terminal.draw(|f| ui(f, &mut app))?;
 
-    if let Some(action) = app.action_rx.recv().await {
-      update(&mut app, action).await;
+    if let Some(mut action) = app.action_rx.recv().await {
+      while action != Action::None {
+        action = update(&mut app, action).await;
+      }
     }
     if app.should_quit {
       break;
     }
+    if let Some(id) = app.selected_item().map(|item| item.id.clone()) {
+      app.unseen.remove(&id);
+    }
     
-    let items_res = app.items_rx.try_recv();
-    match items_res {
-      Ok(items) => {
-        app.items = items;
+    // A list asked before switching between mine and everyone is dropped, the other one is on its way
+    if let Ok(response) = app.items_rx.try_recv() {
+      if response.all_incidents == app.all_incidents {
+        match response.result {
+          Ok(items) => {
+            app.replace_items(items);
+            app.error = None;
+          }
+          Err(err) => app.error = Some(err.to_string()),
+        }
         app.refreshing = false;
       }
-      Err(_) => {}
+    }
+
+    if let Ok(retry) = app.retry_rx.try_recv() {
+      app.retry = Some((retry, Instant::now()));
+    }
+
+    while let Ok(outcome) = app.outcomes_rx.try_recv() {
+      app.finish_mutation(outcome);
+    }
+
+    while let Ok(details) = app.details_rx.try_recv() {
+      if app.mode != Mode::Normal && app.details_id.as_ref() == Some(&details.incident_id) {
+        app.details = Some(details.result);
+      }
+    }
+
+    if let Ok(assignees) = app.assignees_rx.try_recv() {
+      app.picker.entries = Some(assignees);
+    }
+
+    if let Ok(oncalls) = app.oncalls_rx.try_recv() {
+      app.oncalls = Some(oncalls);
+    }
+
+    if let Ok(services) = app.services_rx.try_recv() {
+      app.replace_services(services);
+    }
+
+    if let Ok(windows) = app.maintenance_windows_rx.try_recv() {
+      app.maintenance_windows = Some(windows);
+    }
+
+    if let Ok(outcome) = app.maintenance_outcomes_rx.try_recv() {
+      match outcome.result {
+        Ok(()) => app.load_services().await,
+        Err(err) => {
+          let verb = if outcome.ended { "end" } else { "create" };
+          app.error = Some(format!("Unable to {} the maintenance window: {}", verb, err));
+        }
+      }
+    }
+
+    if let Ok(schedules) = app.schedules_rx.try_recv() {
+      app.replace_schedules(schedules).await;
+    }
+
+    if let Ok(timeline) = app.timeline_rx.try_recv() {
+      // Drop the shifts of a schedule that is not selected anymore
+      let selected_id = app.selected_schedule().map(|schedule| schedule.id.clone());
+      match &timeline {
+        Ok(timeline) if Some(&timeline.schedule_id) != selected_id.as_ref() => {}
+        _ => app.timeline = Some(timeline),
+      }
+    }
+
+    if let Ok(outcome) = app.override_outcomes_rx.try_recv() {
+      match outcome.result {
+        Ok(()) => {
+          // Show the shifts as they are now
+          if app.selected_schedule().is_some_and(|schedule| schedule.id == outcome.schedule_id) {
+            app.load_timeline().await;
+          }
+        }
+        Err(err) => {
+          let verb = if outcome.deleted { "delete" } else { "create" };
+          app.error = Some(format!("Unable to {} the override: {}", verb, err));
+        }
+      }
     }
 
     // REFRESH EVERY X SECOND
@@ -165,35 +908,10 @@ pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io
 
       app.ticker = 0;
 
-      let _res = get_items_async(app.pager_duty.get_pagerduty_domain(),app.pager_duty.get_pagerduty_api_key(), app.all_incidents, app.items_tx.clone()).await;
+      get_items_async(&app.pager_duty, app.all_incidents, app.items_tx.clone()).await;
     } else {
       app.ticker += 1;
     }
   }
   Ok(())
 }
-
-pub fn constraint_len_calculator(items: &[Incident]) -> (u16, u16, u16) {
-  let name_len = items
-    .iter()
-    .map(Incident::status)
-    .map(UnicodeWidthStr::width)
-    .max()
-    .unwrap_or(0);
-  let address_len = items
-    .iter()
-    .map(Incident::summary)
-    .flat_map(str::lines)
-    .map(UnicodeWidthStr::width)
-    .max()
-    .unwrap_or(0);
-  let email_len = items
-    .iter()
-    .map(Incident::created_at)
-    .map(UnicodeWidthStr::width)
-    .max()
-    .unwrap_or(0);
-
-  #[allow(clippy::cast_possible_truncation)]
-  (name_len as u16, address_len as u16, email_len as u16)
-}
@@ -1,21 +1,57 @@
-use std::fs;
+use std::{fs, io};
 use serde::{Deserialize,Serialize};
-use serde_yaml;
 use homedir::get_my_home;
 
-#[derive(Debug, Deserialize, Serialize)]
+use crate::notify::NotificationRule;
+use crate::pagerduty::Mutation;
+use crate::sort::SortColumn;
+use crate::utils::TimeFormat;
+
+const DEFAULT_API_URL: &str = "https://api.pagerduty.com";
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct AppConfig {
   pagerduty_domain: String,
   pagerduty_api_key: String,
   refresh_rate: Option<i64>,
+  api_url: Option<String>,
+  web_url: Option<String>,
+  sort_by: Option<SortColumn>,
+  #[serde(default)]
+  sort_descending: bool,
+  #[serde(default)]
+  time_format: TimeFormat,
+  #[serde(default)]
+  notifications: Vec<NotificationRule>,
+  #[serde(default)]
+  skip_confirmation: Vec<Mutation>,
+}
+
+// What the app remembers between runs, in its own file so the config file is never rewritten
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AppState {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  sort_by: Option<SortColumn>,
+  #[serde(default)]
+  sort_descending: bool,
+}
+
+fn config_dir() -> String {
+  let dir_home:String = String::from(get_my_home().expect("msg").unwrap().to_str().unwrap());
+  format!("{}/.config",dir_home)
+}
+
+fn config_path() -> String {
+  format!("{}/pagerduty_tui.yaml",config_dir())
+}
+
+fn state_path() -> String {
+  format!("{}/pagerduty_tui_state.yaml",config_dir())
 }
 
 impl AppConfig {
   pub fn new() -> Self {
-    let dir_home:String = String::from(get_my_home().expect("msg").unwrap().to_str().unwrap());
-    let config_path = format!("{}/.config/pagerduty_tui.yaml",dir_home);
-
-    let str_config:String = fs::read_to_string(config_path)
+    let str_config:String = fs::read_to_string(config_path())
       .expect("Unable to read YAML config file");
     let config:AppConfig = serde_yaml::from_str(&str_config)
       .expect("Error while parsing YAML config");
@@ -24,16 +60,65 @@ impl AppConfig {
       pagerduty_domain: config.pagerduty_domain,
       pagerduty_api_key: config.pagerduty_api_key,
       refresh_rate: config.refresh_rate,
+      api_url: config.api_url,
+      web_url: config.web_url,
+      sort_by: config.sort_by,
+      sort_descending: config.sort_descending,
+      time_format: config.time_format,
+      notifications: config.notifications,
+      skip_confirmation: config.skip_confirmation,
     }
   }
 
   pub fn get_pagerduty_api_key(&self) -> &str {
     &self.pagerduty_api_key
   }
-  pub fn get_pagerduty_domain(&self) -> &str {
-    &self.pagerduty_domain
+  // REST API, https://api.eu.pagerduty.com for EU accounts
+  pub fn get_api_url(&self) -> &str {
+    self.api_url.as_deref().unwrap_or(DEFAULT_API_URL)
+  }
+  // Web UI used to open the incidents in the browser
+  pub fn get_web_url(&self) -> String {
+    match &self.web_url {
+      Some(web_url) => web_url.clone(),
+      None => format!("https://{}.pagerduty.com", self.pagerduty_domain),
+    }
   }
   pub fn get_refresh_rate(&self) -> &Option<i64> {
     &self.refresh_rate
   }
+  pub fn get_time_format(&self) -> TimeFormat {
+    self.time_format
+  }
+  pub fn get_notifications(&self) -> &[NotificationRule] {
+    &self.notifications
+  }
+  pub fn get_skip_confirmation(&self) -> &[Mutation] {
+    &self.skip_confirmation
+  }
+  pub fn get_sort(&self) -> (Option<SortColumn>, bool) {
+    (self.sort_by, self.sort_descending)
+  }
+}
+
+impl AppState {
+  pub fn new(sort_by: Option<SortColumn>, sort_descending: bool) -> Self {
+    Self { sort_by, sort_descending }
+  }
+
+  // Nothing saved yet, or unreadable, the app starts as the config says
+  pub fn load() -> Option<Self> {
+    let str_state:String = fs::read_to_string(state_path()).ok()?;
+    serde_yaml::from_str(&str_state).ok()
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let str_state:String = serde_yaml::to_string(self)
+      .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(state_path(), str_state)
+  }
+
+  pub fn get_sort(&self) -> (Option<SortColumn>, bool) {
+    (self.sort_by, self.sort_descending)
+  }
 }
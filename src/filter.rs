@@ -0,0 +1,162 @@
+use crate::pagerduty::Incident;
+
+#[derive(PartialEq)]
+enum Field {
+  Any,
+  Summary,
+  Service,
+  Assignee,
+  Number,
+  Status,
+  Priority,
+  Urgency,
+  Policy,
+}
+
+struct Term {
+  field: Field,
+  value: String,
+}
+
+// Words of the search, a quoted value like service:"Payments API" stays in one word
+fn words(text: &str) -> Vec<String> {
+  let mut words: Vec<String> = Vec::new();
+  let mut word = String::new();
+  let mut quoted = false;
+  for c in text.chars() {
+    match c {
+      '"' => quoted = !quoted,
+      c if c.is_whitespace() && !quoted => {
+        if !word.is_empty() {
+          words.push(std::mem::take(&mut word));
+        }
+      }
+      c => word.push(c),
+    }
+  }
+  if !word.is_empty() {
+    words.push(word);
+  }
+  words
+}
+
+// Search typed in the filter bar, like "database service:payments status:triggered"
+pub struct Filter {
+  terms: Vec<Term>,
+}
+
+impl Filter {
+  pub fn parse(text: &str) -> Self {
+    let terms = words(text).iter()
+      .map(|word| {
+        let (field, value) = match word.split_once(':') {
+          Some(("summary", value)) => (Field::Summary, value),
+          Some(("service", value)) => (Field::Service, value),
+          Some(("assignee", value)) => (Field::Assignee, value),
+          Some(("number", value)) => (Field::Number, value),
+          Some(("status", value)) => (Field::Status, value),
+          Some(("priority", value)) => (Field::Priority, value),
+          Some(("urgency", value)) => (Field::Urgency, value),
+          Some(("policy", value)) => (Field::Policy, value),
+          _ => (Field::Any, word.as_str()),
+        };
+        Term { field, value: value.to_lowercase() }
+      })
+      .filter(|term| !term.value.is_empty())
+      .collect();
+
+    Self { terms }
+  }
+
+  // Every term has to match
+  pub fn matches(&self, incident: &Incident) -> bool {
+    let number = incident.number.map(|number| format!("#{}", number)).unwrap_or_default();
+    let priority = incident.priority_name().unwrap_or_default();
+    let urgency = incident.urgency.map(|urgency| urgency.label()).unwrap_or_default();
+
+    self.terms.iter().all(|term| {
+      let contains = |text: &str| text.to_lowercase().contains(&term.value);
+      let assignee = || incident.assignees.iter().any(|assignee| contains(assignee));
+      match term.field {
+        Field::Summary => contains(&incident.summary),
+        Field::Service => contains(&incident.service),
+        Field::Assignee => assignee(),
+        Field::Number => contains(&number),
+        Field::Status => contains(incident.status.label()),
+        Field::Priority => contains(priority),
+        Field::Urgency => contains(urgency),
+        Field::Policy => incident.escalation_policy.as_deref().is_some_and(contains),
+        Field::Any => contains(&incident.summary)
+          || contains(&incident.service)
+          || assignee()
+          || contains(&number)
+          || contains(incident.status.label())
+          || contains(priority),
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::pagerduty::{Priority, Urgency};
+
+  fn incident() -> Incident {
+    Incident {
+      number: Some(42),
+      summary: String::from("Database is down"),
+      service: String::from("Payments API"),
+      urgency: Some(Urgency::High),
+      priority: Some(Priority { name: String::from("P1"), color: String::from("a8171c"), rank: Some(0) }),
+      assignees: vec![String::from("Jane Doe")],
+      escalation_policy: Some(String::from("Infra")),
+      ..Incident::for_test("PABC123")
+    }
+  }
+
+  #[test]
+  fn words_keep_quoted_values_together() {
+    assert_eq!(words(r#"database service:"Payments API"  p1"#), ["database", "service:Payments API", "p1"]);
+    assert!(words("   ").is_empty());
+  }
+
+  #[test]
+  fn empty_filter_matches_everything() {
+    assert!(Filter::parse("").matches(&incident()));
+    assert!(Filter::parse("service:").matches(&incident()));
+  }
+
+  #[test]
+  fn any_field_is_searched_without_a_prefix() {
+    assert!(Filter::parse("DATABASE").matches(&incident()));
+    assert!(Filter::parse("payments").matches(&incident()));
+    assert!(Filter::parse("jane").matches(&incident()));
+    assert!(Filter::parse("#42").matches(&incident()));
+    assert!(!Filter::parse("network").matches(&incident()));
+  }
+
+  #[test]
+  fn prefix_narrows_to_one_field() {
+    assert!(Filter::parse("service:payments").matches(&incident()));
+    assert!(!Filter::parse("summary:payments").matches(&incident()));
+    assert!(Filter::parse("status:trig priority:p1 urgency:high").matches(&incident()));
+    assert!(!Filter::parse("status:acknowledged").matches(&incident()));
+    assert!(Filter::parse("policy:infra assignee:doe number:42").matches(&incident()));
+  }
+
+  #[test]
+  fn quoted_value_matches_with_its_spaces() {
+    assert!(Filter::parse(r#"service:"payments api""#).matches(&incident()));
+    assert!(!Filter::parse(r#"service:"payments web""#).matches(&incident()));
+    // Without quotes the second word is searched in every field
+    assert!(!Filter::parse("service:payments web").matches(&incident()));
+  }
+
+  #[test]
+  fn missing_values_do_not_match() {
+    let incident = Incident { priority: None, escalation_policy: None, ..incident() };
+    assert!(!Filter::parse("priority:p1").matches(&incident));
+    assert!(!Filter::parse("policy:infra").matches(&incident));
+  }
+}
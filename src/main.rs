@@ -1,6 +1,6 @@
 #![allow(clippy::enum_glob_use, clippy::wildcard_imports)]
 
-use std::{env, error::Error, io};
+use std::{env, error::Error, io, process};
 
 use ratatui::prelude::*;
 
@@ -15,9 +15,12 @@ use app::{App,run_app};
 mod actions;
 mod config;
 use config::AppConfig;
+mod filter;
+mod notify;
 mod pagerduty;
 use pagerduty::PagerDuty;
 mod selfupdate;
+mod sort;
 use selfupdate::update_bin;
 mod utils;
 mod ui;
@@ -36,7 +39,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
   let app_config:AppConfig = AppConfig::new();
 
   // Init PD
-  let pd: PagerDuty = PagerDuty::new(app_config.get_pagerduty_domain(),&app_config.get_pagerduty_api_key()).await;
+  let pd: PagerDuty = match PagerDuty::new(app_config.get_api_url(), &app_config.get_web_url(), app_config.get_pagerduty_api_key()).await {
+    Ok(pd) => pd,
+    Err(err) => {
+      eprintln!("Unable to connect to PagerDuty: {}", err);
+      process::exit(1);
+    }
+  };
 
   // setup terminal
   enable_raw_mode()?;
@@ -45,7 +54,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
   let backend = CrosstermBackend::new(stdout);
   let mut terminal = Terminal::new(backend)?;
 
-  let _ = terminal.draw(|f,|splash_screen(f));
+  let _ = terminal.draw(splash_screen);
 
   // create app and run it
   let app = App::new(pd,&app_config).await;
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::process::Command;
+use std::env;
+
+use serde::Deserialize;
+
+use crate::pagerduty::Incident;
+
+// Ways to tell about a new incident
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationMethod {
+  // Terminal bell
+  Bell,
+  // Escape sequence understood by iTerm2, kitty, foot, WezTerm...
+  Osc9,
+  // Escape sequence understood by rxvt-unicode, Konsole, VTE based terminals...
+  Osc777,
+  // Freedesktop notification over D-Bus with notify-send, Notification Center on macOS
+  Desktop,
+}
+
+// Notify with the method the incidents of these services and priorities, all of them when empty
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationRule {
+  pub method: NotificationMethod,
+  #[serde(default)]
+  pub services: Vec<String>,
+  #[serde(default)]
+  pub priorities: Vec<String>,
+}
+
+impl NotificationRule {
+  fn matches(&self, incident: &Incident) -> bool {
+    let service_matches = self.services.is_empty()
+      || self.services.iter().any(|service| service.eq_ignore_ascii_case(&incident.service));
+    let priority_matches = self.priorities.is_empty()
+      || incident.priority_name().is_some_and(|name| self.priorities.iter().any(|priority| priority.eq_ignore_ascii_case(name)));
+    service_matches && priority_matches
+  }
+}
+
+// Incidents triggered in the new list that were not triggered in the old one
+pub fn newly_triggered<'a>(old: &[Incident], new: &'a [Incident]) -> Vec<&'a Incident> {
+  let already_triggered: HashSet<&str> = old.iter()
+    .filter(|incident| incident.triggered())
+    .map(Incident::id)
+    .collect();
+  new.iter()
+    .filter(|incident| incident.triggered() && !already_triggered.contains(incident.id()))
+    .collect()
+}
+
+pub fn notify(rules: &[NotificationRule], incidents: &[&Incident]) {
+  let mut bell = false;
+  let mut sequences = String::new();
+
+  for incident in incidents {
+    let title = format!("PagerDuty: {}", incident.service);
+    let body = match incident.number {
+      Some(number) => format!("#{} {}", number, incident.summary),
+      None => incident.summary.clone(),
+    };
+    // Each method once per incident, even when several rules match
+    let mut methods: Vec<NotificationMethod> = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.matches(incident)) {
+      if !methods.contains(&rule.method) {
+        methods.push(rule.method);
+      }
+    }
+
+    for method in methods {
+      match method {
+        NotificationMethod::Bell => bell = true,
+        NotificationMethod::Osc9 => sequences.push_str(&format!("\x1b]9;{}: {}\x07", strip_controls(&title), strip_controls(&body))),
+        NotificationMethod::Osc777 => sequences.push_str(&format!("\x1b]777;notify;{};{}\x07", strip_controls(&title), strip_controls(&body))),
+        NotificationMethod::Desktop => desktop_notification(title.clone(), body.clone()),
+      }
+    }
+  }
+
+  if bell {
+    sequences.push('\x07');
+  }
+  if !sequences.is_empty() {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(sequences.as_bytes());
+    let _ = stdout.flush();
+  }
+}
+
+// Escape sequences end at the first control character, and ";" separates the fields of OSC 777
+fn strip_controls(text: &str) -> String {
+  text.chars()
+    .map(|c| if c.is_control() || c == ';' { ' ' } else { c })
+    .collect()
+}
+
+fn desktop_notification(title: String, body: String) {
+  // Do not hold the UI while the notification daemon answers
+  tokio::task::spawn_blocking(move || {
+    let os = env::consts::OS;
+
+    if os == "linux" {
+      let _ = Command::new("notify-send")
+        .arg("--app-name=PagerDuty TUI")
+        .arg(title)
+        .arg(body)
+        .output();
+    } else if os == "macos" {
+      let script = format!("display notification {:?} with title {:?}", body, title);
+      let _ = Command::new("/usr/bin/osascript")
+        .arg("-e")
+        .arg(script)
+        .output();
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::pagerduty::{IncidentStatus, Priority};
+
+  fn rule(services: &[&str], priorities: &[&str]) -> NotificationRule {
+    NotificationRule {
+      method: NotificationMethod::Bell,
+      services: services.iter().map(|service| String::from(*service)).collect(),
+      priorities: priorities.iter().map(|priority| String::from(*priority)).collect(),
+    }
+  }
+
+  fn incident(id: &str, service: &str, priority: Option<&str>) -> Incident {
+    Incident {
+      service: String::from(service),
+      priority: priority.map(|name| Priority { name: String::from(name), color: String::new(), rank: None }),
+      ..Incident::for_test(id)
+    }
+  }
+
+  fn ids(incidents: Vec<&Incident>) -> Vec<&str> {
+    incidents.into_iter().map(Incident::id).collect()
+  }
+
+  #[test]
+  fn empty_rule_matches_everything() {
+    assert!(rule(&[], &[]).matches(&incident("a", "Payments", Some("P1"))));
+    assert!(rule(&[], &[]).matches(&incident("a", "", None)));
+  }
+
+  #[test]
+  fn service_rule_ignores_case() {
+    let rule = rule(&["payments", "Auth"], &[]);
+    assert!(rule.matches(&incident("a", "Payments", None)));
+    assert!(rule.matches(&incident("a", "auth", None)));
+    assert!(!rule.matches(&incident("a", "Payments API", None)));
+  }
+
+  #[test]
+  fn priority_rule_needs_a_priority() {
+    let rule = rule(&[], &["p1"]);
+    assert!(rule.matches(&incident("a", "Payments", Some("P1"))));
+    assert!(!rule.matches(&incident("a", "Payments", Some("P2"))));
+    assert!(!rule.matches(&incident("a", "Payments", None)));
+  }
+
+  #[test]
+  fn service_and_priority_rule_needs_both() {
+    let rule = rule(&["Payments"], &["P1"]);
+    assert!(rule.matches(&incident("a", "Payments", Some("P1"))));
+    assert!(!rule.matches(&incident("a", "Auth", Some("P1"))));
+    assert!(!rule.matches(&incident("a", "Payments", Some("P2"))));
+  }
+
+  #[test]
+  fn already_triggered_incidents_are_not_reported_again() {
+    let old = vec![
+      Incident::for_test("kept"),
+      Incident { status: IncidentStatus::Acknowledged, ..Incident::for_test("reopened") },
+    ];
+    let new = vec![
+      Incident::for_test("kept"),
+      Incident::for_test("reopened"),
+      Incident::for_test("new"),
+      Incident { status: IncidentStatus::Acknowledged, ..Incident::for_test("acknowledged") },
+    ];
+    assert_eq!(ids(newly_triggered(&old, &new)), ["reopened", "new"]);
+    assert!(newly_triggered(&new, &new).is_empty());
+  }
+
+  #[test]
+  fn strip_controls_replaces_separators_and_controls() {
+    assert_eq!(strip_controls("Payments; API"), "Payments  API");
+    assert_eq!(strip_controls("down\x07\x1b]9;pwned\n"), "down  ]9 pwned ");
+    assert_eq!(strip_controls("Base de données"), "Base de données");
+  }
+}
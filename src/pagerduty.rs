@@ -1,18 +1,205 @@
-use reqwest::Client;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use reqwest::{Client, Method, Response, StatusCode};
 use reqwest::header::CONTENT_TYPE;
 
 use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
 
-use serde_json;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 
-use crate::utils::split_str;
+use crate::utils::jitter;
 
-const PAGERDUTY_URL: &str = "https://api.pagerduty.com";
 const PAGERDUTY_INCIDENTS_ENDPOINT:&str = "/incidents";
 const PAGERDUTY_USERS_ENDPOINT:&str = "/users";
+const PAGERDUTY_ESCALATION_POLICIES_ENDPOINT:&str = "/escalation_policies";
+const PAGERDUTY_ONCALLS_ENDPOINT:&str = "/oncalls";
+const PAGERDUTY_SCHEDULES_ENDPOINT:&str = "/schedules";
+const PAGERDUTY_SERVICES_ENDPOINT:&str = "/services";
+const PAGERDUTY_MAINTENANCE_WINDOWS_ENDPOINT:&str = "/maintenance_windows";
+const PAGERDUTY_PRIORITIES_ENDPOINT:&str = "/priorities";
+
+const MAINTENANCE_DESCRIPTION: &str = "Maintenance started from PagerDuty TUI";
+
+// Biggest page allowed by the API
+const PAGE_LIMIT: usize = 100;
+// Most incidents updated by one request to the bulk endpoint
+const BULK_LIMIT: usize = 250;
+
+// Retries of a request refused because of the rate limit or a server error
+const MAX_RETRIES: u32 = 4;
+const RETRY_BASE_DELAY_MS: u64 = 500;
+const RETRY_MAX_DELAY_MS: u64 = 30000;
+
+const STATUS_ACKNOWLEDGED: &str = "acknowledged";
+const STATUS_RESOLVED: &str = "resolved";
+
+#[derive(Debug, Clone)]
+pub enum PagerDutyError {
+  // The API key is missing, wrong or expired
+  Auth(StatusCode),
+  RateLimited,
+  // The incident changed in the meantime, like acknowledging a resolved incident
+  Conflict(String),
+  Http(StatusCode, String),
+  Decode(String),
+  Network(String),
+  // Left out of the incidents updated by a bulk request
+  NotUpdated,
+}
+
+impl fmt::Display for PagerDutyError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PagerDutyError::Auth(status) => write!(f, "Authentication refused ({}), check the API key", status),
+      PagerDutyError::RateLimited => write!(f, "Rate limited by PagerDuty"),
+      PagerDutyError::Conflict(message) => write!(f, "Conflict with the current state of the incident: {}", message),
+      PagerDutyError::Http(status, message) if message.is_empty() => write!(f, "PagerDuty answered {}", status),
+      PagerDutyError::Http(status, message) => write!(f, "PagerDuty answered {}: {}", status, message),
+      PagerDutyError::Decode(err) => write!(f, "Unable to read the PagerDuty response: {}", err),
+      PagerDutyError::Network(err) => write!(f, "Unable to reach PagerDuty: {}", err),
+      PagerDutyError::NotUpdated => write!(f, "PagerDuty did not update the incident"),
+    }
+  }
+}
 
-pub const PAGER_DUTY_INCIDENT_URL: &str = ".pagerduty.com/incidents/";
+impl std::error::Error for PagerDutyError {}
+
+impl From<reqwest::Error> for PagerDutyError {
+  fn from(err: reqwest::Error) -> Self {
+    PagerDutyError::Network(err.to_string())
+  }
+}
+
+impl From<serde_json::Error> for PagerDutyError {
+  fn from(err: serde_json::Error) -> Self {
+    PagerDutyError::Decode(err.to_string())
+  }
+}
+
+// Changes made from the TUI, to an incident or to a schedule or maintenance window
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mutation {
+  Acknowledge,
+  Resolve,
+  Reassign,
+  Escalate,
+  Snooze,
+  Note,
+  EndMaintenance,
+  DeleteOverride,
+}
+
+impl Mutation {
+  pub fn label(&self) -> &'static str {
+    match self {
+      Mutation::Acknowledge => "Ack",
+      Mutation::Resolve => "Resolve",
+      Mutation::Reassign => "Reassign",
+      Mutation::Escalate => "Escalate",
+      Mutation::Snooze => "Snooze",
+      Mutation::Note => "Note",
+      Mutation::EndMaintenance => "End maintenance",
+      Mutation::DeleteOverride => "Delete override",
+    }
+  }
+}
+
+// Sent back to the UI once PagerDuty answered a mutation
+pub struct MutationOutcome {
+  pub incident_id: String,
+  pub mutation: Mutation,
+  pub result: Result<(),PagerDutyError>,
+}
+
+// Sent back to the UI once PagerDuty answered the creation or the deletion of an override
+pub struct OverrideOutcome {
+  pub schedule_id: String,
+  pub deleted: bool,
+  pub result: Result<(),PagerDutyError>,
+}
+
+// Sent back to the UI once PagerDuty answered the creation or the end of a maintenance window
+pub struct MaintenanceOutcome {
+  pub ended: bool,
+  pub result: Result<(),PagerDutyError>,
+}
+
+// Incidents from a refresh, with whether they are the ones of everyone or only mine
+pub struct ItemsResponse {
+  pub all_incidents: bool,
+  pub result: Result<Vec<Incident>,PagerDutyError>,
+}
+
+// Details of an incident, with the incident they were asked for
+pub struct DetailsResponse {
+  pub incident_id: String,
+  pub result: Result<IncidentDetails,PagerDutyError>,
+}
+
+// Sent to the UI when a request is going to be sent again
+pub struct RetryNotice {
+  pub status: StatusCode,
+  pub delay: Duration,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyErrorBody{
+  error: PagerDutyErrorMessage,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyErrorMessage{
+  message: String,
+  #[serde(default)]
+  errors: Vec<String>,
+}
+
+// Turn a response that is not a success into an error, with the message sent by PagerDuty
+async fn error_from_response(response: Response) -> PagerDutyError {
+  let status = response.status();
+  match status {
+    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => PagerDutyError::Auth(status),
+    StatusCode::TOO_MANY_REQUESTS => PagerDutyError::RateLimited,
+    _ => {
+      let body_bytes = response.bytes().await.unwrap_or_default();
+      let message = match serde_json::from_slice::<PagerDutyErrorBody>(&body_bytes) {
+        Ok(body) if body.error.errors.is_empty() => body.error.message,
+        Ok(body) => format!("{} ({})", body.error.message, body.error.errors.join(", ")),
+        Err(_) => String::new(),
+      };
+      if status == StatusCode::CONFLICT {
+        PagerDutyError::Conflict(message)
+      } else {
+        PagerDutyError::Http(status, message)
+      }
+    }
+  }
+}
+
+// Wait asked by the API before the next request, in seconds
+fn retry_hint(response: &Response) -> Option<u64> {
+  ["retry-after", "ratelimit-reset"].iter()
+    .filter_map(|header| response.headers().get(*header))
+    .filter_map(|value| value.to_str().ok())
+    .find_map(|value| value.trim().parse::<u64>().ok())
+}
+
+// One page of a list endpoint, the list itself is under a key named after the resource
+#[derive(Debug, Deserialize)]
+struct PagerDutyPage{
+  #[serde(default)]
+  more: bool,
+  total: Option<usize>,
+  #[serde(flatten)]
+  lists: serde_json::Map<String, serde_json::Value>,
+}
 
 #[derive(Debug, Deserialize)]
 struct PagerDutyUserResult{
@@ -25,6 +212,7 @@ struct PagerDutyUser{
 
 #[derive(Debug, Deserialize)]
 struct PagerDutyService{
+  id: String,
   summary: String,
 }
 #[derive(Debug, Deserialize)]
@@ -37,227 +225,1090 @@ struct PagerDutyAssignment{
 }
 #[derive(Debug, Deserialize)]
 struct PagerDutyPriority {
+  id: String,
+  summary: String,
+  #[serde(default)]
+  color: String,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyReference{
+  summary: String,
 }
 
 #[derive(Debug, Deserialize)]
-struct PagerDutyIncidents{
-  incidents: Vec<PagerDutyIncident>
+struct PagerDutyNamedReference{
+  id: String,
+  name: String,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyIncidentBody{
+  details: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-struct PagerDutyIncident {
+struct PagerDutyIncidentResult{
+  incident: PagerDutyIncident,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyOnCall{
+  escalation_policy: PagerDutyNamedSummary,
+  escalation_level: u64,
+  schedule: Option<PagerDutyNamedSummary>,
+  user: PagerDutyNamedSummary,
+  start: Option<DateTime<Utc>>,
+  end: Option<DateTime<Utc>>,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyNamedSummary{
   id: String,
   summary: String,
-  created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyServiceDetails{
+  id: String,
+  name: String,
+  status: ServiceStatus,
+  escalation_policy: Option<PagerDutyReference>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyMaintenanceWindow{
+  id: String,
+  start_time: DateTime<Utc>,
+  end_time: DateTime<Utc>,
+  description: Option<String>,
+  #[serde(default)]
+  services: Vec<PagerDutyService>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutySchedule{
+  id: String,
+  name: String,
+  #[serde(default)]
+  time_zone: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyScheduleResult{
+  schedule: PagerDutyRenderedSchedule,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyRenderedSchedule{
+  final_schedule: PagerDutySubSchedule,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutySubSchedule{
+  #[serde(default)]
+  rendered_schedule_entries: Vec<PagerDutyScheduleEntry>,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyScheduleEntry{
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+  user: PagerDutyNamedSummary,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyOverrides{
+  overrides: Vec<PagerDutyOverride>,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyOverride{
+  id: String,
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+  user: PagerDutyNamedSummary,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyIncidentsUpdated{
+  incidents: Vec<PagerDutyIncidentId>,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyIncidentId{
+  id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyAlert{
+  summary: String,
   status: String,
+  created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyLogEntry{
+  summary: String,
+  created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyNotes{
+  notes: Vec<PagerDutyNote>,
+}
+#[derive(Debug, Deserialize)]
+struct PagerDutyNote{
+  content: String,
+  created_at: DateTime<Utc>,
+  user: Option<PagerDutyReference>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyPendingAction{
+  #[serde(rename = "type")]
+  action_type: String,
+  at: DateTime<Utc>,
+}
+
+
+#[derive(Debug, Deserialize)]
+struct PagerDutyIncident {
+  id: String,
+  summary: String,
+  created_at: DateTime<Utc>,
+  status: IncidentStatus,
   service: PagerDutyService,
   priority: Option<PagerDutyPriority>,
   assignments: Vec<PagerDutyAssignment>,
+  incident_number: Option<u64>,
+  urgency: Option<Urgency>,
+  body: Option<PagerDutyIncidentBody>,
+  escalation_level: Option<u64>,
+  escalation_policy: Option<PagerDutyReference>,
+  #[serde(default)]
+  html_url: String,
+  #[serde(default)]
+  pending_actions: Vec<PagerDutyPendingAction>,
+}
+
+// Ordered from the most to the least urgent to handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentStatus {
+  Triggered,
+  Acknowledged,
+  Resolved,
 }
 
+impl IncidentStatus {
+  pub fn label(&self) -> &'static str {
+    match self {
+      IncidentStatus::Triggered => "triggered",
+      IncidentStatus::Acknowledged => STATUS_ACKNOWLEDGED,
+      IncidentStatus::Resolved => STATUS_RESOLVED,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+  High,
+  Low,
+}
+
+impl Urgency {
+  pub fn label(&self) -> &'static str {
+    match self {
+      Urgency::High => "high",
+      Urgency::Low => "low",
+    }
+  }
+}
+
+#[derive(PartialEq)]
+pub struct Priority {
+  pub name: String,
+  pub color: String, // hex color set in PagerDuty, like "a8171c"
+  pub rank: Option<usize>, // 0 for the most severe, as ordered in PagerDuty
+}
+
+
+#[derive(PartialEq)]
 pub struct Incident {
   pub id: String,
+  pub number: Option<u64>,
   pub summary: String,
+  pub service_id: String,
   pub service: String,
-  pub status: String,
-  pub created_at: String,
-  pub triggered: bool,
+  pub status: IncidentStatus,
+  pub urgency: Option<Urgency>,
+  pub priority: Option<Priority>,
+  pub created_at: DateTime<Utc>,
+  pub assignees: Vec<String>,
+  pub escalation_policy: Option<String>,
+  pub html_url: String,
+  pub retrigger_at: Option<DateTime<Utc>>,
 }
 
 impl Incident {
-  pub const fn ref_array(&self) -> [&String; 3] {
-    [&self.status, &self.summary, &self.created_at]
+  pub fn id(&self) -> &str {
+    &self.id
   }
 
-  pub fn status(&self) -> &str {
-    &self.status
+  pub fn triggered(&self) -> bool {
+    self.status == IncidentStatus::Triggered
   }
 
-  pub fn summary(&self) -> &str {
-    &self.summary
+  // First assignee, the one shown in the table
+  pub fn assignee(&self) -> Option<&str> {
+    self.assignees.first().map(String::as_str)
   }
 
-  pub fn created_at(&self) -> &str {
-    &self.created_at
+  pub fn priority_name(&self) -> Option<&str> {
+    self.priority.as_ref().map(|priority| priority.name.as_str())
   }
+}
 
-  pub fn id(&self) -> &str {
-    &self.id
+// Triggered incident with nothing but an id, the tests fill in the fields they need
+#[cfg(test)]
+impl Incident {
+  pub fn for_test(id: &str) -> Self {
+    Incident {
+      id: String::from(id),
+      number: None,
+      summary: String::new(),
+      service_id: String::new(),
+      service: String::new(),
+      status: IncidentStatus::Triggered,
+      urgency: None,
+      priority: None,
+      created_at: DateTime::UNIX_EPOCH,
+      assignees: Vec::new(),
+      escalation_policy: None,
+      html_url: String::new(),
+      retrigger_at: None,
+    }
+  }
+}
+
+pub struct IncidentAlert {
+  pub summary: String,
+  pub status: String,
+  pub created_at: DateTime<Utc>,
+}
+
+pub struct LogEntry {
+  pub summary: String,
+  pub created_at: DateTime<Utc>,
+}
+
+pub struct Note {
+  pub content: String,
+  pub user: String,
+  pub created_at: DateTime<Utc>,
+}
+
+// Everything shown in the detail pane of an incident
+pub struct IncidentDetails {
+  pub number: Option<u64>,
+  pub summary: String,
+  pub service: String,
+  pub status: IncidentStatus,
+  pub urgency: Option<Urgency>,
+  pub created_at: DateTime<Utc>,
+  pub assignees: Vec<String>,
+  pub details: String,
+  pub alerts: Vec<IncidentAlert>,
+  pub log_entries: Vec<LogEntry>,
+  pub notes: Vec<Note>,
+}
+
+// Shift of a user at a level of an escalation policy, without start and end when it is permanent
+pub struct OnCall {
+  pub escalation_policy_id: String,
+  pub escalation_policy: String,
+  pub level: u64,
+  pub schedule: Option<String>,
+  pub user_id: String,
+  pub user: String,
+  pub start: Option<DateTime<Utc>>,
+  pub end: Option<DateTime<Utc>>,
+}
+
+impl OnCall {
+  pub fn is_current(&self, now: &DateTime<Utc>) -> bool {
+    self.start.is_none_or(|start| start <= *now) && self.end.is_none_or(|end| *now < end)
   }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceStatus {
+  Active,
+  Warning,
+  Critical,
+  Maintenance,
+  Disabled,
+}
 
-  pub fn triggered(&self) -> &bool {
-    &self.triggered
+impl ServiceStatus {
+  pub fn label(&self) -> &'static str {
+    match self {
+      ServiceStatus::Active => "Active",
+      ServiceStatus::Warning => "Warning",
+      ServiceStatus::Critical => "Critical",
+      ServiceStatus::Maintenance => "Maintenance",
+      ServiceStatus::Disabled => "Disabled",
+    }
   }
+}
 
+// Service with the number of its open incidents, whoever they are assigned to
+pub struct Service {
+  pub id: String,
+  pub name: String,
+  pub status: ServiceStatus,
+  pub escalation_policy: Option<String>,
+  pub triggered: usize,
+  pub acknowledged: usize,
 }
 
-pub struct PagerDuty {
+// Time the incidents of some services are not sent to anyone
+pub struct MaintenanceWindow {
+  pub id: String,
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+  pub description: String,
+  pub services: Vec<String>,
+}
+
+impl MaintenanceWindow {
+  pub fn is_ongoing(&self, now: &DateTime<Utc>) -> bool {
+    self.start <= *now && *now < self.end
+  }
+}
+
+pub struct Schedule {
+  pub id: String,
+  pub name: String,
+  pub time_zone: String,
+}
+
+// Someone on call in a schedule between two times, the override it comes from if any
+pub struct ScheduleEntry {
+  pub user_id: String,
+  pub user: String,
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+  pub override_id: Option<String>,
+}
+
+// Shifts of a schedule between since and until, as PagerDuty works them out with the overrides
+pub struct ScheduleTimeline {
+  pub schedule_id: String,
+  pub since: DateTime<Utc>,
+  pub until: DateTime<Utc>,
+  pub entries: Vec<ScheduleEntry>,
+  pub overrides: Vec<ScheduleEntry>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AssigneeKind {
+  User,
+  EscalationPolicy,
+}
+
+// Someone an incident can be assigned to
+#[derive(Clone)]
+pub struct Assignee {
+  pub id: String,
+  pub name: String,
+  pub kind: AssigneeKind,
+}
+
+struct PagerDutyInner {
+  client: Client,
+  api_url: String,
+  web_url: String,
   api_key: String,
   current_user_id: String,
-  domain: String,
+  priority_ids: OnceLock<Vec<String>>, // from the most to the least severe, empty when priorities are turned off
+  retry_tx: OnceLock<mpsc::UnboundedSender<RetryNotice>>,
+}
+
+impl PagerDutyInner {
+  // Send a request, with an exponential backoff when PagerDuty is rate limiting or failing
+  async fn request(&self, method: Method, path: &str, body: Option<&serde_json::Value>) -> Result<Response,PagerDutyError> {
+    let mut attempt: u32 = 0;
+
+    loop {
+      let mut request = self.client.request(method.clone(), format!("{}{}", self.api_url, path))
+        .header(CONTENT_TYPE, "application/json")
+        .header("Accept", "application/json")
+        .header("Authorization", format!("Token token={}", self.api_key));
+      if let Some(body) = body {
+        request = request.body(body.to_string());
+      }
+      let response = request.send().await?;
+
+      let status = response.status();
+      if status.is_success() {
+        return Ok(response);
+      }
+      // A POST that failed on the way back may have been applied, sending it again could create a duplicate
+      let retryable = status == StatusCode::TOO_MANY_REQUESTS || (status.is_server_error() && method != Method::POST);
+      if attempt >= MAX_RETRIES || !retryable {
+        return Err(error_from_response(response).await);
+      }
+
+      let backoff_ms = (RETRY_BASE_DELAY_MS << attempt).min(RETRY_MAX_DELAY_MS);
+      let delay_ms = match retry_hint(&response) {
+        Some(seconds) => seconds.saturating_mul(1000).min(RETRY_MAX_DELAY_MS),
+        None => backoff_ms,
+      };
+      let delay = Duration::from_millis(delay_ms + jitter(RETRY_BASE_DELAY_MS));
+      if let Some(retry_tx) = self.retry_tx.get() {
+        let _ = retry_tx.send(RetryNotice { status, delay });
+      }
+      tokio::time::sleep(delay).await;
+      attempt += 1;
+    }
+  }
+
+  async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T,PagerDutyError> {
+    let response = self.request(Method::GET, path, None).await?;
+
+    let body_bytes = response.bytes().await?;
+    let body = String::from_utf8_lossy(&body_bytes);
+
+    Ok(serde_json::from_str(&body)?)
+  }
+}
+
+// Cheap to clone into tasks, every clone shares the connection pool and the current user
+#[derive(Clone)]
+pub struct PagerDuty {
+  inner: Arc<PagerDutyInner>,
 }
 impl PagerDuty {
-  pub async fn new(domain: &str, api_key: &str) -> Self{
-    Self {
-      domain: String::from(domain),
+  pub async fn new(api_url: &str, web_url: &str, api_key: &str) -> Result<Self,PagerDutyError>{
+    let mut inner = PagerDutyInner {
+      client: Client::new(),
+      api_url: String::from(api_url.trim_end_matches('/')),
+      web_url: String::from(web_url.trim_end_matches('/')),
       api_key: String::from(api_key),
-      current_user_id: get_current_user_id(api_key).await.expect("Error getting current user id"),
-    }
+      current_user_id: String::new(),
+      priority_ids: OnceLock::new(),
+      retry_tx: OnceLock::new(),
+    };
+    let current_user_path:String = format!("{}/me",PAGERDUTY_USERS_ENDPOINT);
+    let current_user:PagerDutyUserResult = inner.get(&current_user_path).await?;
+    inner.current_user_id = current_user.user.id;
+    let pd = Self { inner: Arc::new(inner) };
+    // Priorities are optional in PagerDuty, without them incidents have none to sort by
+    let priority_ids = pd.get_all::<PagerDutyPriority>(PAGERDUTY_PRIORITIES_ENDPOINT, "priorities").await
+      .map(|priorities| priorities.into_iter().map(|priority| priority.id).collect())
+      .unwrap_or_default();
+    let _ = pd.inner.priority_ids.set(priority_ids);
+
+    Ok(pd)
   }
 
-  pub fn get_pagerduty_api_key(&self) -> &str {
-    &self.api_key
+  pub fn incident_url(&self, id: &str) -> String {
+    format!("{}{}/{}", self.inner.web_url, PAGERDUTY_INCIDENTS_ENDPOINT, id)
   }
-  pub fn get_pagerduty_domain(&self) -> &str {
-    &self.domain
+
+  pub fn schedule_url(&self, id: &str) -> String {
+    format!("{}{}/{}", self.inner.web_url, PAGERDUTY_SCHEDULES_ENDPOINT, id)
   }
 
-  pub async fn get_incidents(&self, all_incidents: bool) -> Result<Vec<Incident>, String> {
-    let statuses: [&str; 2] = ["triggered","acknowledged"];
-    let mut pd_incidents: Vec<PagerDutyIncident> = Vec::new();
+  pub fn current_user_id(&self) -> &str {
+    &self.inner.current_user_id
+  }
 
-    for status in statuses {
-      let url_requets:String;
-      if all_incidents {
-        url_requets = format!("{}{}?statuses[]={}&limit=100",
-            PAGERDUTY_URL,PAGERDUTY_INCIDENTS_ENDPOINT, status);
-      } else {
-        url_requets = format!("{}{}?statuses[]={}&user_ids[]={}&limit=100",
-            PAGERDUTY_URL,PAGERDUTY_INCIDENTS_ENDPOINT, status, &self.current_user_id);
-      }
-      let client = Client::new();
-      let response = client.get(&url_requets)
-          .header(CONTENT_TYPE, "application/json")
-          .header("Accept", "application/json")
-          .header("Authorization", format!("Token token={}", &self.api_key))
-          .send().await.expect("Error sending the API request to PagerDuty");
-      if response.status().is_success() {
-        let body_bytes = response.bytes().await.expect("Error while parsing PD response");
-        let body = String::from_utf8_lossy(&body_bytes);
-
-        let mut pd_incidents_buf:PagerDutyIncidents = serde_json::from_str(&body).expect("Error parsing result");
-        pd_incidents_buf.incidents.reverse();
-        pd_incidents.append(&mut pd_incidents_buf.incidents);
-      }
+  // Tell the UI each time a request waits before being sent again
+  pub fn set_retry_tx(&self, retry_tx: mpsc::UnboundedSender<RetryNotice>) {
+    let _ = self.inner.retry_tx.set(retry_tx);
+  }
 
-    }
+  async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T,PagerDutyError> {
+    self.inner.get(path).await
+  }
+
+  async fn send(&self, method: Method, path: &str, body: serde_json::Value) -> Result<(),PagerDutyError> {
+    self.inner.request(method, path, Some(&body)).await?;
+    Ok(())
+  }
+
+  // Walk every page of a list endpoint, the pages are fetched at the same time when the total is known
+  async fn get_all<T: DeserializeOwned>(&self, path: &str, key: &str) -> Result<Vec<T>,PagerDutyError> {
+    let separator = if path.contains('?') { '&' } else { '?' };
+    let page_path = |offset: usize| format!("{}{}limit={}&offset={}&total=true", path, separator, PAGE_LIMIT, offset);
+
+    let mut page: PagerDutyPage = self.get(&page_path(0)).await?;
+    let mut pages: Vec<PagerDutyPage> = Vec::new();
 
-    let mut incidents_result: Vec<Incident> = Vec::new();
-    for mut incident in pd_incidents {
-      // Shorten summary to fit
-      if incident.summary.len() > 100 {
-        incident.summary = split_str(incident.summary, 100);
+    if let (true, Some(total)) = (page.more, page.total) {
+      let mut requests = JoinSet::new();
+      for offset in (PAGE_LIMIT..total).step_by(PAGE_LIMIT) {
+        let pd = self.clone();
+        let path = page_path(offset);
+        requests.spawn(async move { (offset, pd.get::<PagerDutyPage>(&path).await) });
       }
-      // Emergency
-      if incident.priority.is_some() {
-        incident.status = String::from("/!\\ P1 /!\\");
+      let mut results: Vec<(usize, Result<PagerDutyPage,PagerDutyError>)> = Vec::new();
+      while let Some(result) = requests.join_next().await {
+        // Only a panic of the request task ends here
+        results.push(result.map_err(|err| PagerDutyError::Network(err.to_string()))?);
       }
+      results.sort_by_key(|(offset, _)| *offset);
 
-      let mut triggered: bool = false;
-      // Triggered
-      if incident.status == "triggered" {
-        triggered = true;
+      pages.push(page);
+      for (_, result) in results {
+        pages.push(result?);
       }
-      
-      // Assignee
-      let assignee: String;
-      if incident.assignments.len() > 0 {
-        assignee = incident.assignments.get(0).unwrap().assignee.summary.clone();
+    } else {
+      let mut offset = 0;
+      while page.more {
+        offset += PAGE_LIMIT;
+        let next_page: PagerDutyPage = self.get(&page_path(offset)).await?;
+        pages.push(page);
+        page = next_page;
       }
-      else {
-        assignee = String::from("----------");
-      }
-      let created_at_str: String;
-      created_at_str = String::from(format!("{}\n{}", incident.created_at, assignee));
-
-      incident.service.summary = format!("{}", incident.service.summary);
-      // Prepare the text to show
-      incident.summary = format!("{}\n{}", incident.service.summary,incident.summary);
-
-      incidents_result.push(Incident {
-        id: incident.id,
-        summary: incident.summary,
-        service: incident.service.summary,
-        status: incident.status,
-        created_at: created_at_str,
-        triggered: triggered,
-      });
+      pages.push(page);
     }
 
-    if incidents_result.len() == 0 {
-      let empty_incident: Incident = Incident {
-        id: String::from("---------"),
-        summary: String::from(" - NO INCIDENTS | TIME FOR A BREAK - "),
-        service: String::from(""),
-        status: String::from("---------"),
-        created_at: String::from("---------"),
-        triggered: false,
+    let mut items: Vec<T> = Vec::new();
+    for mut page in pages {
+      let list = page.lists.remove(key).unwrap_or_default();
+      items.append(&mut serde_json::from_value(list)?);
+    }
+    Ok(items)
+  }
+
+  pub async fn get_incidents(&self, all_incidents: bool) -> Result<Vec<Incident>, PagerDutyError> {
+    let statuses: [&str; 2] = ["triggered","acknowledged"];
+    let mut pd_incidents: Vec<PagerDutyIncident> = Vec::new();
+
+    for status in statuses {
+      let path:String = if all_incidents {
+        format!("{}?statuses[]={}",
+            PAGERDUTY_INCIDENTS_ENDPOINT, status)
+      } else {
+        format!("{}?statuses[]={}&user_ids[]={}",
+            PAGERDUTY_INCIDENTS_ENDPOINT, status, &self.inner.current_user_id)
       };
-      incidents_result.push(empty_incident)
+      let mut pd_incidents_buf = self.get_all::<PagerDutyIncident>(&path, "incidents").await?;
+      pd_incidents_buf.reverse();
+      pd_incidents.append(&mut pd_incidents_buf);
     }
-    
-    //incidents_result.reverse();
-    Ok(incidents_result)
-    
+
+    let incidents = pd_incidents.into_iter()
+      .map(|incident| {
+        // Snoozed or acknowledged on a service with an acknowledgement timeout, PagerDuty
+        // schedules the incident to go back to triggered the same way for both
+        let retrigger_at = incident.pending_actions.iter()
+          .find(|pending_action| pending_action.action_type == "unacknowledge")
+          .map(|pending_action| pending_action.at);
+        Incident {
+          id: incident.id,
+          number: incident.incident_number,
+          summary: incident.summary,
+          service_id: incident.service.id,
+          service: incident.service.summary,
+          status: incident.status,
+          urgency: incident.urgency,
+          priority: incident.priority.map(|priority| Priority {
+            rank: self.inner.priority_ids.get().and_then(|ids| ids.iter().position(|id| *id == priority.id)),
+            name: priority.summary,
+            color: priority.color,
+          }),
+          created_at: incident.created_at,
+          assignees: incident.assignments.into_iter().map(|assignment| assignment.assignee.summary).collect(),
+          escalation_policy: incident.escalation_policy.map(|policy| policy.summary),
+          html_url: incident.html_url,
+          retrigger_at,
+        }
+      })
+      .collect();
+    Ok(incidents)
   }
 
-}
+  pub async fn get_incident_details(&self, id: &str) -> Result<IncidentDetails,PagerDutyError> {
+    let incident_path:String = format!("{}/{}",PAGERDUTY_INCIDENTS_ENDPOINT, id);
+    let alerts_path:String = format!("{}/alerts", incident_path);
+    let log_entries_path:String = format!("{}/log_entries?is_overview=true", incident_path);
+    let notes_path:String = format!("{}/notes", incident_path);
 
-async fn get_current_user_id(api_key: &str) -> Result<String,String> {
-  let current_user_requets:String = format!("{}{}/me",PAGERDUTY_URL,PAGERDUTY_USERS_ENDPOINT);
+    let (incident_res, alerts_res, log_entries_res, notes_res) = tokio::join!(
+      self.get::<PagerDutyIncidentResult>(&incident_path),
+      self.get_all::<PagerDutyAlert>(&alerts_path, "alerts"),
+      self.get_all::<PagerDutyLogEntry>(&log_entries_path, "log_entries"),
+      self.get::<PagerDutyNotes>(&notes_path),
+    );
+    let incident = incident_res?.incident;
 
-  let client = Client::new();
-  let response = client.get(&current_user_requets)
-    .header(CONTENT_TYPE, "application/json")
-    .header("Accept", "application/json")
-    .header("Authorization", format!("Token token={}", api_key))
-    .send().await.expect("Error sending the API request to PagerDuty");
+    Ok(IncidentDetails {
+      number: incident.incident_number,
+      summary: incident.summary,
+      service: incident.service.summary,
+      status: incident.status,
+      urgency: incident.urgency,
+      created_at: incident.created_at,
+      assignees: incident.assignments.into_iter().map(|assignment| assignment.assignee.summary).collect(),
+      details: incident.body.and_then(|body| body.details).unwrap_or_default(),
+      alerts: alerts_res?.into_iter().map(|alert| IncidentAlert {
+        summary: alert.summary,
+        status: alert.status,
+        created_at: alert.created_at,
+      }).collect(),
+      log_entries: log_entries_res?.into_iter().map(|log_entry| LogEntry {
+        summary: log_entry.summary,
+        created_at: log_entry.created_at,
+      }).collect(),
+      notes: notes_res?.notes.into_iter().map(|note| Note {
+        content: note.content,
+        user: note.user.map(|user| user.summary).unwrap_or_default(),
+        created_at: note.created_at,
+      }).collect(),
+    })
+  }
 
-  if response.status().is_success() {
-    let body_bytes = response.bytes().await.expect("Error while parsing PD response");
-    let body = String::from_utf8_lossy(&body_bytes);
+  async fn put_incident(&self, id: &str, mut incident: serde_json::Value) -> Result<(),PagerDutyError> {
+    let incident_path:String = format!("{}/{}",PAGERDUTY_INCIDENTS_ENDPOINT, id);
 
-    let current_user:PagerDutyUserResult = serde_json::from_str(&body).expect("Error parsing result");
-    Ok(current_user.user.id)
-  } else {
-    Err(response.status().to_string())
+    incident["type"] = serde_json::Value::from("incident_reference");
+    self.send(Method::PUT, &incident_path, serde_json::json!({ "incident": incident })).await
+  }
+
+  // Shifts from now to the given number of days
+  pub async fn get_oncalls(&self, days: i64) -> Result<Vec<OnCall>,PagerDutyError> {
+    let since = Utc::now();
+    let until = since + TimeDelta::days(days);
+    let path:String = format!("{}?since={}&until={}",
+        PAGERDUTY_ONCALLS_ENDPOINT,
+        since.to_rfc3339_opts(SecondsFormat::Secs, true),
+        until.to_rfc3339_opts(SecondsFormat::Secs, true));
+
+    let oncalls = self.get_all::<PagerDutyOnCall>(&path, "oncalls").await?;
+    Ok(oncalls.into_iter()
+      .map(|oncall| OnCall {
+        escalation_policy_id: oncall.escalation_policy.id,
+        escalation_policy: oncall.escalation_policy.summary,
+        level: oncall.escalation_level,
+        schedule: oncall.schedule.map(|schedule| schedule.summary),
+        user_id: oncall.user.id,
+        user: oncall.user.summary,
+        start: oncall.start,
+        end: oncall.end,
+      })
+      .collect())
+  }
+
+  pub async fn get_services(&self) -> Result<Vec<Service>,PagerDutyError> {
+    let (services_res, incidents_res) = tokio::join!(
+      self.get_all::<PagerDutyServiceDetails>(PAGERDUTY_SERVICES_ENDPOINT, "services"),
+      self.get_incidents(true),
+    );
+    let incidents = incidents_res?;
+    let count = |service_id: &str, status: IncidentStatus| incidents.iter()
+      .filter(|incident| incident.service_id == service_id && incident.status == status)
+      .count();
+
+    Ok(services_res?.into_iter()
+      .map(|service| Service {
+        triggered: count(&service.id, IncidentStatus::Triggered),
+        acknowledged: count(&service.id, IncidentStatus::Acknowledged),
+        id: service.id,
+        name: service.name,
+        status: service.status,
+        escalation_policy: service.escalation_policy.map(|policy| policy.summary),
+      })
+      .collect())
+  }
+
+  // Ongoing and future maintenance windows, the soonest first
+  pub async fn get_maintenance_windows(&self) -> Result<Vec<MaintenanceWindow>,PagerDutyError> {
+    let ongoing_path:String = format!("{}?filter=ongoing", PAGERDUTY_MAINTENANCE_WINDOWS_ENDPOINT);
+    let future_path:String = format!("{}?filter=future", PAGERDUTY_MAINTENANCE_WINDOWS_ENDPOINT);
+    let (ongoing_res, future_res) = tokio::join!(
+      self.get_all::<PagerDutyMaintenanceWindow>(&ongoing_path, "maintenance_windows"),
+      self.get_all::<PagerDutyMaintenanceWindow>(&future_path, "maintenance_windows"),
+    );
+
+    let mut windows: Vec<MaintenanceWindow> = ongoing_res?.into_iter()
+      .chain(future_res?)
+      .map(|window| MaintenanceWindow {
+        id: window.id,
+        start: window.start_time,
+        end: window.end_time,
+        description: window.description.unwrap_or_default(),
+        services: window.services.into_iter().map(|service| service.summary).collect(),
+      })
+      .collect();
+    windows.sort_by_key(|window| window.start);
+    Ok(windows)
+  }
+
+  // Maintenance of a service from now on
+  pub async fn create_maintenance_window(&self, service_id: &str, seconds: u64) -> Result<(),PagerDutyError> {
+    let start = Utc::now();
+    let end = i64::try_from(seconds).ok()
+      .and_then(TimeDelta::try_seconds)
+      .and_then(|duration| start.checked_add_signed(duration))
+      .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let body = serde_json::json!({
+      "maintenance_window": {
+        "type": "maintenance_window",
+        "start_time": start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "end_time": end.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "description": MAINTENANCE_DESCRIPTION,
+        "services": [{
+          "id": service_id,
+          "type": "service_reference",
+        }]
+      }
+    });
+    self.send(Method::POST, PAGERDUTY_MAINTENANCE_WINDOWS_ENDPOINT, body).await
+  }
+
+  // Ends an ongoing window now, a future one is deleted
+  pub async fn end_maintenance_window(&self, id: &str) -> Result<(),PagerDutyError> {
+    let window_path:String = format!("{}/{}", PAGERDUTY_MAINTENANCE_WINDOWS_ENDPOINT, id);
+    self.inner.request(Method::DELETE, &window_path, None).await?;
+    Ok(())
   }
-}
 
-pub async fn acknowledge_async(api_key: &str, id: &str) -> Result<(), ()> {
-  let url_requet:String = format!("{}{}/{}",PAGERDUTY_URL,PAGERDUTY_INCIDENTS_ENDPOINT, id);
+  pub async fn get_schedules(&self) -> Result<Vec<Schedule>,PagerDutyError> {
+    let schedules = self.get_all::<PagerDutySchedule>(PAGERDUTY_SCHEDULES_ENDPOINT, "schedules").await?;
+    Ok(schedules.into_iter()
+      .map(|schedule| Schedule {
+        id: schedule.id,
+        name: schedule.name,
+        time_zone: schedule.time_zone,
+      })
+      .collect())
+  }
+
+  // Shifts of the schedule from now to the given number of days
+  pub async fn get_schedule_timeline(&self, id: &str, days: i64) -> Result<ScheduleTimeline,PagerDutyError> {
+    let since = Utc::now();
+    let until = since + TimeDelta::days(days);
+    let range = format!("since={}&until={}",
+        since.to_rfc3339_opts(SecondsFormat::Secs, true),
+        until.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let schedule_path:String = format!("{}/{}?{}", PAGERDUTY_SCHEDULES_ENDPOINT, id, range);
+    let overrides_path:String = format!("{}/{}/overrides?{}", PAGERDUTY_SCHEDULES_ENDPOINT, id, range);
+
+    let (schedule_res, overrides_res) = tokio::join!(
+      self.get::<PagerDutyScheduleResult>(&schedule_path),
+      self.get::<PagerDutyOverrides>(&overrides_path),
+    );
+    let overrides: Vec<ScheduleEntry> = overrides_res?.overrides.into_iter()
+      .map(|schedule_override| ScheduleEntry {
+        user_id: schedule_override.user.id,
+        user: schedule_override.user.summary,
+        start: schedule_override.start,
+        end: schedule_override.end,
+        override_id: Some(schedule_override.id),
+      })
+      .collect();
+
+    // The final schedule already has the overrides in it, find where each entry comes from
+    let entries = schedule_res?.schedule.final_schedule.rendered_schedule_entries.into_iter()
+      .map(|entry| {
+        let override_id = overrides.iter()
+          .find(|schedule_override| schedule_override.user_id == entry.user.id
+            && schedule_override.start <= entry.start && entry.end <= schedule_override.end)
+          .and_then(|schedule_override| schedule_override.override_id.clone());
+        ScheduleEntry {
+          user_id: entry.user.id,
+          user: entry.user.summary,
+          start: entry.start,
+          end: entry.end,
+          override_id,
+        }
+      })
+      .collect();
+
+    Ok(ScheduleTimeline { schedule_id: String::from(id), since, until, entries, overrides })
+  }
 
-  let api_key_moved = String::from(api_key);
+  // Someone else on call in the schedule between start and end
+  pub async fn create_override(&self, schedule_id: &str, user_id: &str, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Result<(),PagerDutyError> {
+    let overrides_path:String = format!("{}/{}/overrides", PAGERDUTY_SCHEDULES_ENDPOINT, schedule_id);
+    let body = serde_json::json!({
+      "override": {
+        "start": start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "end": end.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "user": {
+          "id": user_id,
+          "type": "user_reference",
+        }
+      }
+    });
+    self.send(Method::POST, &overrides_path, body).await
+  }
+
+  pub async fn delete_override(&self, schedule_id: &str, override_id: &str) -> Result<(),PagerDutyError> {
+    let override_path:String = format!("{}/{}/overrides/{}", PAGERDUTY_SCHEDULES_ENDPOINT, schedule_id, override_id);
+    self.inner.request(Method::DELETE, &override_path, None).await?;
+    Ok(())
+  }
+
+  // Same change on several incidents, answers the ids of the incidents PagerDuty updated
+  async fn put_incidents(&self, ids: &[String], incident: &serde_json::Value) -> Result<HashSet<String>,PagerDutyError> {
+    let incidents: Vec<serde_json::Value> = ids.iter()
+      .map(|id| {
+        let mut incident = incident.clone();
+        incident["id"] = serde_json::Value::from(id.as_str());
+        incident["type"] = serde_json::Value::from("incident_reference");
+        incident
+      })
+      .collect();
+    let body = serde_json::json!({ "incidents": incidents });
+
+    let response = self.inner.request(Method::PUT, PAGERDUTY_INCIDENTS_ENDPOINT, Some(&body)).await?;
+    let body_bytes = response.bytes().await?;
+    let updated: PagerDutyIncidentsUpdated = serde_json::from_slice(&body_bytes)?;
+    Ok(updated.incidents.into_iter().map(|incident| incident.id).collect())
+  }
+
+  pub async fn get_assignees(&self) -> Result<Vec<Assignee>,PagerDutyError> {
+    let (users_res, escalation_policies_res) = tokio::join!(
+      self.get_all::<PagerDutyNamedReference>(PAGERDUTY_USERS_ENDPOINT, "users"),
+      self.get_all::<PagerDutyNamedReference>(PAGERDUTY_ESCALATION_POLICIES_ENDPOINT, "escalation_policies"),
+    );
+
+    let mut assignees: Vec<Assignee> = users_res?.into_iter()
+      .map(|user| Assignee { id: user.id, name: user.name, kind: AssigneeKind::User })
+      .collect();
+    assignees.extend(escalation_policies_res?.into_iter()
+      .map(|policy| Assignee { id: policy.id, name: policy.name, kind: AssigneeKind::EscalationPolicy }));
+
+    Ok(assignees)
+  }
+
+}
+
+pub async fn update_incident_async(pd: &PagerDuty, id: &str, incident: serde_json::Value, mutation: Mutation, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  let pd = pd.clone();
+  let incident_id = String::from(id);
 
   tokio::spawn(async move {
-    let client = Client::new();
-    let _response = client.put(&url_requet)
-      .header(CONTENT_TYPE, "application/json")
-      .header("Accept", "application/json")
-      .header("Authorization", format!("Token token={}", api_key_moved))
-      .body("{\n  \"incident\": {\n    \"type\": \"incident_reference\",\n    \"status\": \"acknowledged\"\n  }\n}")
-      .send().await.expect("Error sending the API request to PagerDuty");
+    let result = pd.put_incident(&incident_id, incident).await;
+    outcomes_tx.send(MutationOutcome { incident_id, mutation, result })
   });
-  
-  Ok(())
 }
 
-pub async fn get_items_async(domain: &str, api_key: &str, all_incidents: bool, tx: mpsc::UnboundedSender<Vec<Incident>>) -> Result<(), ()> {
-  let pd_api_key = String::from(api_key);
-  let pd_domain = String::from(domain);
+// Update the incidents with the bulk endpoint, the outcome of each incident is sent on its own
+pub async fn bulk_update_async(pd: &PagerDuty, ids: Vec<String>, incident: serde_json::Value, mutation: Mutation, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  let pd = pd.clone();
 
   tokio::spawn(async move {
-    let pd = PagerDuty::new(&pd_domain, &pd_api_key).await;
-    let items_res = pd.get_incidents(all_incidents).await;
-    match items_res {
-      Ok(items) => {
-        tx.send(items)
+    for chunk in ids.chunks(BULK_LIMIT) {
+      let updated = pd.put_incidents(chunk, &incident).await;
+      for incident_id in chunk {
+        let result = match &updated {
+          Ok(updated) if updated.contains(incident_id) => Ok(()),
+          Ok(_) => Err(PagerDutyError::NotUpdated),
+          Err(err) => Err(err.clone()),
+        };
+        let _ = outcomes_tx.send(MutationOutcome { incident_id: incident_id.clone(), mutation, result });
       }
-      Err(_) => {
-        tx.send(Vec::new())
+    }
+  });
+}
+
+pub async fn bulk_acknowledge_async(pd: &PagerDuty, ids: Vec<String>, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  bulk_update_async(pd, ids, serde_json::json!({ "status": STATUS_ACKNOWLEDGED }), Mutation::Acknowledge, outcomes_tx).await
+}
+
+pub async fn bulk_resolve_async(pd: &PagerDuty, ids: Vec<String>, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  bulk_update_async(pd, ids, serde_json::json!({ "status": STATUS_RESOLVED }), Mutation::Resolve, outcomes_tx).await
+}
+
+pub async fn bulk_reassign_async(pd: &PagerDuty, ids: Vec<String>, assignee: &Assignee, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  bulk_update_async(pd, ids, reassign_body(assignee), Mutation::Reassign, outcomes_tx).await
+}
+
+pub async fn acknowledge_async(pd: &PagerDuty, id: &str, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  update_incident_async(pd, id, serde_json::json!({ "status": STATUS_ACKNOWLEDGED }), Mutation::Acknowledge, outcomes_tx).await
+}
+
+pub async fn resolve_async(pd: &PagerDuty, id: &str, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  update_incident_async(pd, id, serde_json::json!({ "status": STATUS_RESOLVED }), Mutation::Resolve, outcomes_tx).await
+}
+
+fn reassign_body(assignee: &Assignee) -> serde_json::Value {
+  match assignee.kind {
+    AssigneeKind::User => serde_json::json!({
+      "assignments": [{
+        "assignee": { "id": assignee.id, "type": "user_reference" }
+      }]
+    }),
+    AssigneeKind::EscalationPolicy => serde_json::json!({
+      "escalation_policy": { "id": assignee.id, "type": "escalation_policy_reference" }
+    }),
+  }
+}
+
+pub async fn reassign_async(pd: &PagerDuty, id: &str, assignee: &Assignee, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  update_incident_async(pd, id, reassign_body(assignee), Mutation::Reassign, outcomes_tx).await
+}
+
+pub async fn escalate_async(pd: &PagerDuty, id: &str, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  let incident_path:String = format!("{}/{}",PAGERDUTY_INCIDENTS_ENDPOINT, id);
+
+  let pd = pd.clone();
+  let incident_id = String::from(id);
+
+  tokio::spawn(async move {
+    // The next level is relative to where the incident is now
+    let result = match pd.get::<PagerDutyIncidentResult>(&incident_path).await {
+      Ok(incident_result) => {
+        let escalation_level = incident_result.incident.escalation_level.unwrap_or(1) + 1;
+        pd.put_incident(&incident_id, serde_json::json!({ "escalation_level": escalation_level })).await
       }
+      Err(err) => Err(err),
+    };
+    outcomes_tx.send(MutationOutcome { incident_id, mutation: Mutation::Escalate, result })
+  });
+}
+
+pub async fn snooze_async(pd: &PagerDuty, id: &str, duration: u64, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  let snooze_path:String = format!("{}/{}/snooze",PAGERDUTY_INCIDENTS_ENDPOINT, id);
+
+  let pd = pd.clone();
+  let incident_id = String::from(id);
+
+  tokio::spawn(async move {
+    let result = pd.send(Method::POST, &snooze_path, serde_json::json!({ "duration": duration })).await;
+    outcomes_tx.send(MutationOutcome { incident_id, mutation: Mutation::Snooze, result })
+  });
+}
+
+pub async fn get_assignees_async(pd: &PagerDuty, tx: mpsc::UnboundedSender<Result<Vec<Assignee>,PagerDutyError>>) {
+  let pd = pd.clone();
+
+  tokio::spawn(async move {
+    tx.send(pd.get_assignees().await)
+  });
+}
+
+pub async fn get_items_async(pd: &PagerDuty, all_incidents: bool, tx: mpsc::UnboundedSender<ItemsResponse>) {
+  let pd = pd.clone();
+
+  tokio::spawn(async move {
+    let result = pd.get_incidents(all_incidents).await;
+    tx.send(ItemsResponse { all_incidents, result })
+  });
+}
+
+pub async fn get_oncalls_async(pd: &PagerDuty, days: i64, tx: mpsc::UnboundedSender<Result<Vec<OnCall>,PagerDutyError>>) {
+  let pd = pd.clone();
+
+  tokio::spawn(async move {
+    tx.send(pd.get_oncalls(days).await)
+  });
+}
+
+pub async fn get_services_async(pd: &PagerDuty, tx: mpsc::UnboundedSender<Result<Vec<Service>,PagerDutyError>>) {
+  let pd = pd.clone();
+
+  tokio::spawn(async move {
+    tx.send(pd.get_services().await)
+  });
+}
+
+pub async fn get_maintenance_windows_async(pd: &PagerDuty, tx: mpsc::UnboundedSender<Result<Vec<MaintenanceWindow>,PagerDutyError>>) {
+  let pd = pd.clone();
+
+  tokio::spawn(async move {
+    tx.send(pd.get_maintenance_windows().await)
+  });
+}
+
+pub async fn create_maintenance_window_async(pd: &PagerDuty, service_id: &str, seconds: u64, tx: mpsc::UnboundedSender<MaintenanceOutcome>) {
+  let pd = pd.clone();
+  let service_id = String::from(service_id);
+
+  tokio::spawn(async move {
+    let result = pd.create_maintenance_window(&service_id, seconds).await;
+    tx.send(MaintenanceOutcome { ended: false, result })
+  });
+}
+
+pub async fn end_maintenance_window_async(pd: &PagerDuty, id: &str, tx: mpsc::UnboundedSender<MaintenanceOutcome>) {
+  let pd = pd.clone();
+  let window_id = String::from(id);
+
+  tokio::spawn(async move {
+    let result = pd.end_maintenance_window(&window_id).await;
+    tx.send(MaintenanceOutcome { ended: true, result })
+  });
+}
+
+pub async fn get_schedules_async(pd: &PagerDuty, tx: mpsc::UnboundedSender<Result<Vec<Schedule>,PagerDutyError>>) {
+  let pd = pd.clone();
+
+  tokio::spawn(async move {
+    tx.send(pd.get_schedules().await)
+  });
+}
+
+pub async fn get_schedule_timeline_async(pd: &PagerDuty, id: &str, days: i64, tx: mpsc::UnboundedSender<Result<ScheduleTimeline,PagerDutyError>>) {
+  let pd = pd.clone();
+  let schedule_id = String::from(id);
+
+  tokio::spawn(async move {
+    tx.send(pd.get_schedule_timeline(&schedule_id, days).await)
+  });
+}
+
+pub async fn create_override_async(pd: &PagerDuty, schedule_id: &str, user_id: &str, start: DateTime<Utc>, end: DateTime<Utc>, tx: mpsc::UnboundedSender<OverrideOutcome>) {
+  let pd = pd.clone();
+  let schedule_id = String::from(schedule_id);
+  let user_id = String::from(user_id);
+
+  tokio::spawn(async move {
+    let result = pd.create_override(&schedule_id, &user_id, &start, &end).await;
+    tx.send(OverrideOutcome { schedule_id, deleted: false, result })
+  });
+}
+
+pub async fn delete_override_async(pd: &PagerDuty, schedule_id: &str, override_id: &str, tx: mpsc::UnboundedSender<OverrideOutcome>) {
+  let pd = pd.clone();
+  let schedule_id = String::from(schedule_id);
+  let override_id = String::from(override_id);
+
+  tokio::spawn(async move {
+    let result = pd.delete_override(&schedule_id, &override_id).await;
+    tx.send(OverrideOutcome { schedule_id, deleted: true, result })
+  });
+}
+
+pub async fn get_details_async(pd: &PagerDuty, id: &str, tx: mpsc::UnboundedSender<DetailsResponse>) {
+  let pd = pd.clone();
+  let incident_id = String::from(id);
+
+  tokio::spawn(async move {
+    let result = pd.get_incident_details(&incident_id).await;
+    tx.send(DetailsResponse { incident_id, result })
+  });
+}
+
+pub async fn add_note_async(pd: &PagerDuty, id: &str, content: &str, details_tx: Option<mpsc::UnboundedSender<DetailsResponse>>, outcomes_tx: mpsc::UnboundedSender<MutationOutcome>) {
+  let notes_path:String = format!("{}/{}/notes",PAGERDUTY_INCIDENTS_ENDPOINT, id);
+
+  let pd = pd.clone();
+  let incident_id = String::from(id);
+  let body = serde_json::json!({
+    "note": {
+      "content": content,
     }
   });
 
-  Ok(())
+  tokio::spawn(async move {
+    let result = pd.send(Method::POST, &notes_path, body).await;
+    let _ = outcomes_tx.send(MutationOutcome { incident_id: incident_id.clone(), mutation: Mutation::Note, result });
+    // Reload the incident open in the pane so the new note shows in its history
+    if let Some(tx) = details_tx {
+      let result = pd.get_incident_details(&incident_id).await;
+      let _ = tx.send(DetailsResponse { incident_id, result });
+    }
+  });
 }
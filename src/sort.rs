@@ -0,0 +1,185 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pagerduty::Incident;
+
+// Columns the incident table can be sorted by, saved in the config as `sort_by`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+  CreatedAt,
+  Service,
+  Priority,
+  Urgency,
+  Status,
+  Assignee,
+}
+
+impl SortColumn {
+  pub fn label(&self) -> &'static str {
+    match self {
+      SortColumn::CreatedAt => "created at",
+      SortColumn::Service => "service",
+      SortColumn::Priority => "priority",
+      SortColumn::Urgency => "urgency",
+      SortColumn::Status => "status",
+      SortColumn::Assignee => "assignee",
+    }
+  }
+
+  // Order used by the sort key, back to the order of PagerDuty after the last column
+  pub fn next(column: Option<SortColumn>) -> Option<SortColumn> {
+    match column {
+      None => Some(SortColumn::CreatedAt),
+      Some(SortColumn::CreatedAt) => Some(SortColumn::Service),
+      Some(SortColumn::Service) => Some(SortColumn::Priority),
+      Some(SortColumn::Priority) => Some(SortColumn::Urgency),
+      Some(SortColumn::Urgency) => Some(SortColumn::Status),
+      Some(SortColumn::Status) => Some(SortColumn::Assignee),
+      Some(SortColumn::Assignee) => None,
+    }
+  }
+
+  // Empty values stay last in both orders
+  pub fn compare(&self, a: &Incident, b: &Incident, descending: bool) -> Ordering {
+    match self {
+      SortColumn::CreatedAt => directed(a.created_at.cmp(&b.created_at), descending),
+      SortColumn::Service => directed(a.service.to_lowercase().cmp(&b.service.to_lowercase()), descending),
+      SortColumn::Priority => empty_last(priority_key(a), priority_key(b), descending),
+      SortColumn::Urgency => empty_last(a.urgency, b.urgency, descending),
+      SortColumn::Status => directed(a.status.cmp(&b.status), descending),
+      SortColumn::Assignee => empty_last(
+        a.assignee().map(str::to_lowercase),
+        b.assignee().map(str::to_lowercase),
+        descending,
+      ),
+    }
+  }
+}
+
+// Most severe first as ordered in PagerDuty, by name for a priority PagerDuty did not list
+fn priority_key(incident: &Incident) -> Option<(usize, String)> {
+  incident.priority.as_ref().map(|priority| (priority.rank.unwrap_or(usize::MAX), priority.name.to_lowercase()))
+}
+
+fn directed(ordering: Ordering, descending: bool) -> Ordering {
+  if descending { ordering.reverse() } else { ordering }
+}
+
+fn empty_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+  match (a, b) {
+    (Some(a), Some(b)) => directed(a.cmp(&b), descending),
+    (Some(_), None) => Ordering::Less,
+    (None, Some(_)) => Ordering::Greater,
+    (None, None) => Ordering::Equal,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeDelta;
+
+  use super::*;
+  use crate::pagerduty::{IncidentStatus, Priority, Urgency};
+
+  fn with_priority(id: &str, name: &str, rank: Option<usize>) -> Incident {
+    let priority = Priority { name: String::from(name), color: String::new(), rank };
+    Incident { priority: Some(priority), ..Incident::for_test(id) }
+  }
+
+  // Ids in the order the column sorts the incidents
+  fn sorted(column: SortColumn, mut incidents: Vec<Incident>, descending: bool) -> Vec<String> {
+    incidents.sort_by(|a, b| column.compare(a, b, descending));
+    incidents.into_iter().map(|incident| incident.id).collect()
+  }
+
+  #[test]
+  fn next_goes_through_every_column_and_back() {
+    let mut column = SortColumn::next(None);
+    let mut seen = Vec::new();
+    while let Some(current) = column {
+      seen.push(current);
+      column = SortColumn::next(column);
+    }
+    assert_eq!(seen.len(), 6);
+  }
+
+  #[test]
+  fn created_at_in_both_orders() {
+    let incidents = || {
+      let old = Incident::for_test("old");
+      let new = Incident { created_at: old.created_at + TimeDelta::hours(1), ..Incident::for_test("new") };
+      vec![new, old]
+    };
+    assert_eq!(sorted(SortColumn::CreatedAt, incidents(), false), ["old", "new"]);
+    assert_eq!(sorted(SortColumn::CreatedAt, incidents(), true), ["new", "old"]);
+  }
+
+  #[test]
+  fn service_ignores_case() {
+    let incidents = || vec![
+      Incident { service: String::from("billing"), ..Incident::for_test("b") },
+      Incident { service: String::from("Auth"), ..Incident::for_test("a") },
+    ];
+    assert_eq!(sorted(SortColumn::Service, incidents(), false), ["a", "b"]);
+    assert_eq!(sorted(SortColumn::Service, incidents(), true), ["b", "a"]);
+  }
+
+  #[test]
+  fn priority_by_rank_then_unranked_by_name() {
+    let incidents = || vec![
+      with_priority("p3", "P3", Some(2)),
+      with_priority("custom", "Custom", None),
+      with_priority("p1", "P1", Some(0)),
+      with_priority("other", "Another", None),
+      with_priority("p2", "P2", Some(1)),
+    ];
+    assert_eq!(sorted(SortColumn::Priority, incidents(), false), ["p1", "p2", "p3", "other", "custom"]);
+    assert_eq!(sorted(SortColumn::Priority, incidents(), true), ["custom", "other", "p3", "p2", "p1"]);
+  }
+
+  #[test]
+  fn priority_empty_last_in_both_orders() {
+    let incidents = || vec![
+      Incident::for_test("none"),
+      with_priority("p2", "P2", Some(1)),
+      with_priority("p1", "P1", Some(0)),
+    ];
+    assert_eq!(sorted(SortColumn::Priority, incidents(), false), ["p1", "p2", "none"]);
+    assert_eq!(sorted(SortColumn::Priority, incidents(), true), ["p2", "p1", "none"]);
+  }
+
+  #[test]
+  fn urgency_empty_last_in_both_orders() {
+    let incidents = || vec![
+      Incident::for_test("none"),
+      Incident { urgency: Some(Urgency::Low), ..Incident::for_test("low") },
+      Incident { urgency: Some(Urgency::High), ..Incident::for_test("high") },
+    ];
+    assert_eq!(sorted(SortColumn::Urgency, incidents(), false), ["high", "low", "none"]);
+    assert_eq!(sorted(SortColumn::Urgency, incidents(), true), ["low", "high", "none"]);
+  }
+
+  #[test]
+  fn assignee_empty_last_in_both_orders() {
+    let incidents = || vec![
+      Incident::for_test("none"),
+      Incident { assignees: vec![String::from("zoe"), String::from("Adam")], ..Incident::for_test("zoe") },
+      Incident { assignees: vec![String::from("Bob")], ..Incident::for_test("bob") },
+    ];
+    assert_eq!(sorted(SortColumn::Assignee, incidents(), false), ["bob", "zoe", "none"]);
+    assert_eq!(sorted(SortColumn::Assignee, incidents(), true), ["zoe", "bob", "none"]);
+  }
+
+  #[test]
+  fn status_from_the_most_urgent() {
+    let incidents = || vec![
+      Incident { status: IncidentStatus::Resolved, ..Incident::for_test("resolved") },
+      Incident::for_test("triggered"),
+      Incident { status: IncidentStatus::Acknowledged, ..Incident::for_test("acknowledged") },
+    ];
+    assert_eq!(sorted(SortColumn::Status, incidents(), false), ["triggered", "acknowledged", "resolved"]);
+    assert_eq!(sorted(SortColumn::Status, incidents(), true), ["resolved", "acknowledged", "triggered"]);
+  }
+}
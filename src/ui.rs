@@ -2,23 +2,210 @@ use ratatui::{
   prelude::*, symbols::border, widgets::{block::title::*, *}
 };
 
-use crate::{app::App, pagerduty::Incident};
+use reqwest::StatusCode;
+use unicode_width::UnicodeWidthStr;
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, TimeDelta, Utc};
+
+use crate::{app::{App, ConfirmAction, Mode, Tab, ONCALL_DAYS, DURATIONS}, pagerduty::{AssigneeKind, Incident, IncidentDetails, MaintenanceWindow, OnCall, ScheduleEntry, ScheduleTimeline, ServiceStatus, Urgency}, sort::SortColumn, utils::{format_duration, format_time, parse_local_time, split_str, TimeFormat}};
 
 const INFO_TEXT: &str =
-  "(Esc) Quit | (↑/↓/🏠) Navigate | (R) Refresh | (Space) Ack | (A) Ack service | (G) Show all | (Enter) Open";
+  "(Esc) Quit | (Tab) Next tab | (↑/↓/🏠) Navigate | (R) Refresh | (Space/V/*) Mark/range/all | (A/Shift+A) Ack/service | (X/Shift+X) Resolve/service | (G) Show all | (D) Details | (N) Note | (Shift+R) Reassign | (E) Escalate | (Z) Snooze | (M) Maintenance | (/) Search | (S/Shift+S) Sort/order | (Enter) Open";
+
+const DETAIL_INFO_TEXT: &str =
+  "(Esc) Close details | (↑/↓) Scroll | (N) Note | (Shift+R) Reassign | (E) Escalate | (Enter) Open";
+
+const NOTE_INFO_TEXT: &str =
+  "(Esc) Cancel | (Enter) New line | (Ctrl+S) Save note";
+
+const ONCALL_INFO_TEXT: &str =
+  "(Esc) Quit | (Tab) Next tab | (↑/↓) Scroll | (R) Refresh | (G) Show all escalation policies";
+
+const SERVICES_INFO_TEXT: &str =
+  "(Esc) Quit | (Tab) Next tab | (↑/↓) Navigate | (R) Refresh | (M) Maintenance | (E) End maintenance | (Enter) Show the incidents of the service";
+
+const MAINTENANCE_INFO_TEXT: &str =
+  "(Esc) Cancel | (↑/↓) Navigate | (Enter) Start maintenance | Type a custom duration (90m, 2h, 1d)";
+
+const END_MAINTENANCE_INFO_TEXT: &str =
+  "(Esc) Cancel | (↑/↓) Navigate | (Enter) End maintenance";
+
+const SCHEDULES_INFO_TEXT: &str =
+  "(Esc) Quit | (Tab) Next tab | (↑/↓) Navigate | (R) Refresh | (W) 7/14 days | (C) Create override | (X) Delete override | (Enter) Open";
+
+const OVERRIDE_FORM_INFO_TEXT: &str =
+  "(Esc) Cancel | (Tab) Start/end | (Enter) Create override | Times like 2024-03-01 18:00, Fri 18:00 or 18:00";
+
+const DELETE_OVERRIDE_INFO_TEXT: &str =
+  "(Esc) Cancel | (↑/↓) Navigate | (Enter) Delete override";
+
+const CONFIRM_INFO_TEXT: &str =
+  "(Y/Enter) Confirm | (N/Esc) Cancel";
+
+const PICKER_INFO_TEXT: &str =
+  "(Esc) Cancel | (↑/↓) Navigate | (Enter) Select | Type to search";
+
+const SNOOZE_INFO_TEXT: &str =
+  "(Esc) Cancel | (↑/↓) Navigate | (Enter) Snooze | Type a custom duration (90m, 2h, 1d)";
 
 const SPLASH_TEXT: &str = " ____   __    ___  ____  ____    ____  _  _  ____  _  _ \n(  _ \\ / _\\  / __)(  __)(  _ \\  (    \\/ )( \\(_  _)( \\/ )\n ) __//    \\( (_ \\ ) _)  )   /   ) D () \\/ (  )(   )  / \n(__)  \\_/\\_/ \\___/(____)(__\\_)  (____/\\____/ (__) (__/  ";
 
 pub fn ui(f: &mut Frame, app: &mut App) {
-  let rects = Layout::vertical([Constraint::Min(5), Constraint::Length(3)]).split(f.size());
+  let error_height: u16 = if app.error.is_some() { 3 } else { 0 };
+  let rects = Layout::vertical([Constraint::Length(1), Constraint::Min(5), Constraint::Length(error_height), Constraint::Length(3)]).split(f.size());
 
   app.set_colors();
 
-  render_table(f, app, rects[0]);
+  render_tabs(f, app, rects[0]);
+
+  if app.tab == Tab::OnCall {
+    render_oncalls(f, app, rects[1]);
+  } else if app.tab == Tab::Services {
+    #[allow(clippy::cast_possible_truncation)]
+    let windows_height = (app.maintenance_windows().len().max(1) as u16).min(8) + 2;
+    let panes = Layout::vertical([Constraint::Min(5), Constraint::Length(windows_height)]).split(rects[1]);
+
+    render_services(f, app, panes[0]);
+
+    render_scrollbar(f, &mut app.services_scroll_state, panes[0]);
+
+    render_maintenance_windows(f, app, panes[1]);
+  } else if app.tab == Tab::Schedules {
+    let panes = Layout::horizontal([Constraint::Percentage(25), Constraint::Percentage(75)]).split(rects[1]);
+
+    render_schedules(f, app, panes[0]);
+
+    render_scrollbar(f, &mut app.schedules_scroll_state, panes[0]);
+
+    render_timeline(f, app, panes[1]);
+  } else if app.mode == Mode::Detail || app.details.is_some() {
+    let panes = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).split(rects[1]);
+
+    render_table(f, app, panes[0]);
+
+    render_scrollbar(f, &mut app.scroll_state, panes[0]);
+
+    render_details(f, app, panes[1]);
+  } else {
+    render_table(f, app, rects[1]);
+
+    render_scrollbar(f, &mut app.scroll_state, rects[1]);
+  }
+
+  render_error(f, app, rects[2]);
+
+  render_footer(f, app, rects[3]);
+
+  match app.mode {
+    Mode::Note => render_note_composer(f, app),
+    Mode::Picker => render_picker(f, app),
+    Mode::Snooze => {
+      let title = match app.target_incident_ids.len() {
+        1 => String::from(" - Snooze for - "),
+        count => format!(" - Snooze {} incidents for - ", count),
+      };
+      render_duration_prompt(f, app, title);
+    },
+    Mode::Maintenance => {
+      let service = app.maintenance_service.as_ref().map(|(_, name)| name.as_str()).unwrap_or_default();
+      render_duration_prompt(f, app, format!(" - Maintenance of {} for - ", service));
+    },
+    Mode::EndMaintenance => render_end_maintenance(f, app),
+    Mode::Confirm => render_confirmation(f, app),
+    Mode::OverrideForm => render_override_form(f, app),
+    Mode::DeleteOverride => render_delete_override(f, app),
+    _ => {},
+  }
+}
+
+pub fn render_tabs(f: &mut Frame, app: &App, area: Rect) {
+  let titles: Vec<String> = Tab::ALL.iter().map(|tab| format!(" {} ", tab.title())).collect();
+  let selected = Tab::ALL.iter().position(|tab| *tab == app.tab).unwrap_or(0);
+  let tabs = Tabs::new(titles)
+    .select(selected)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .highlight_style(Style::new().fg(app.colors.header_fg).bg(app.colors.header_bg).bold());
+  f.render_widget(tabs, area);
+}
+
+// Area in the middle of the screen for a popup
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+  let vertical = Layout::vertical([
+    Constraint::Percentage((100 - percent_y) / 2),
+    Constraint::Percentage(percent_y),
+    Constraint::Percentage((100 - percent_y) / 2),
+  ]).split(area);
+
+  Layout::horizontal([
+    Constraint::Percentage((100 - percent_x) / 2),
+    Constraint::Percentage(percent_x),
+    Constraint::Percentage((100 - percent_x) / 2),
+  ]).split(vertical[1])[1]
+}
+
+// Color of a priority as sent by PagerDuty, like "a8171c"
+fn priority_color(hex: &str) -> Option<Color> {
+  if hex.len() != 6 {
+    return None;
+  }
+  let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+  Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn is_light(color: Color) -> bool {
+  match color {
+    Color::Rgb(r, g, b) => 299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b) > 150_000,
+    _ => false,
+  }
+}
+
+// Longest summary shown in the table
+const SUMMARY_MAX_LEN: usize = 100;
+
+fn format_number(number: Option<u64>) -> String {
+  number.map(|number| format!("#{}", number)).unwrap_or_default()
+}
+
+// Text of the cells of an incident, in the order of the columns
+fn incident_cells(item: &Incident, progress: Option<&String>, time_format: TimeFormat) -> [String; 5] {
+  let mut status = String::from(item.status.label());
+  if let Some(retrigger_at) = &item.retrigger_at {
+    status = format!("{}\nBack to triggered at {}", status, format_time(retrigger_at, time_format));
+  }
+  if let Some(progress) = progress {
+    status = format!("{}\n{}", status, progress);
+  }
 
-  render_scrollbar(f, app, rects[0]);
+  // Shorten summary to fit
+  let summary = if item.summary.chars().count() > SUMMARY_MAX_LEN {
+    split_str(item.summary.clone(), SUMMARY_MAX_LEN as u16)
+  } else {
+    item.summary.clone()
+  };
+
+  [
+    status,
+    String::from(item.priority_name().unwrap_or_default()),
+    String::from(item.urgency.map(|urgency| urgency.label()).unwrap_or_default()),
+    format!("{}\n{}", item.service, summary),
+    format!("{}\n{}", format_time(&item.created_at, time_format), item.assignee().unwrap_or("----------")),
+  ]
+}
 
-  render_footer(f, app, rects[1]);
+// Widest line of each column
+fn column_widths(cells: &[[String; 5]]) -> [u16; 5] {
+  let mut widths = [0; 5];
+  for row in cells {
+    for (width, content) in widths.iter_mut().zip(row) {
+      let content_width = content.lines().map(UnicodeWidthStr::width).max().unwrap_or(0);
+      #[allow(clippy::cast_possible_truncation)]
+      let content_width = content_width as u16;
+      *width = (*width).max(content_width);
+    }
+  }
+  widths
 }
 
 pub fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
@@ -29,50 +216,103 @@ pub fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
     .add_modifier(Modifier::REVERSED)
     .fg(app.colors.selected_style_fg);
 
-  let header = ["STATUS", "SERVICE - SUMMARY", "CREATED AT"]
+  // Only the rows left by the filter and the hidden acks
+  let cells: Vec<[String; 5]> = app.visible.iter()
+    .map(|&item_index| {
+      let item = &app.items[item_index];
+      incident_cells(item, app.progress.get(&item.id), app.time_format)
+    })
+    .collect();
+  let lens = column_widths(&cells);
+
+  let widths = [
+    // + 1 is for padding.
+    Constraint::Length(lens[0].max(9) + 1),
+    Constraint::Length(lens[1].max(10) + 1),
+    Constraint::Length(9 + 1),
+    Constraint::Min(lens[3] + 1),
+    Constraint::Min(lens[4]),
+  ];
+  let sortable = [SortColumn::Status, SortColumn::Priority, SortColumn::Urgency, SortColumn::Service, SortColumn::CreatedAt];
+  let arrow = if app.sort_descending { " ▼" } else { " ▲" };
+  let header = ["STATUS", "PRIORITY", "URGENCY", "SERVICE - SUMMARY", "CREATED AT"]
     .into_iter()
-    .map(Cell::from)
+    .zip(sortable)
+    .map(|(name, column)| {
+      if app.sort_by == Some(column) {
+        Cell::from(format!("{}{}", name, arrow))
+      } else {
+        Cell::from(name)
+      }
+    })
     .collect::<Row>()
     .style(header_style)
     .height(1);
 
   // Creating rows for table
   let mut rows: Vec<Row> = Vec::new();
-  for i in 0..app.items.len() {
-    let item = app.items.get(i).unwrap();
-    // If item is ack and the ack are not hide
-    if (!!!*item.triggered() && !!!app.hide_ack ) || (*item.triggered()) {
-      let color:Color;
-
-      if *item.triggered(){
-        color = match i % 2 {
-          0 => app.colors.triggered_normal_color,
-          _ => app.colors.triggered_alt_color,
-        };
-      } else {
-        color = match i % 2 {
-          0 => app.colors.normal_row_color,
-          _ => app.colors.alt_row_color,
-        };
-      }
-      
-      rows.push(item.ref_array().into_iter()
-        .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
-        .collect::<Row>()
-        .style(Style::new().fg(app.colors.row_fg).bg(color))
-        .height(4));
-    }
-  }
-  if rows.len() == 0 {
-    let empty_item: Incident = Incident {
-      id: String::from("---------"),
-      summary: String::from(" - NO INCIDENTS | TIME FOR A BREAK - "),
-      status: String::from("---------"),
-      service: String::from(""),
-      created_at: String::from("---------"),
-      triggered: false, 
+  let marked_ids = app.marked_ids();
+  for (i, (&item_index, row_cells)) in app.visible.iter().zip(cells).enumerate() {
+    let item = &app.items[item_index];
+    let priority_color = item.priority.as_ref().and_then(|priority| priority_color(&priority.color));
+    // Triggered rows take the color of their priority, or of their urgency without one
+    let color:Color = match (item.triggered(), priority_color) {
+      (true, Some(color)) => color,
+      (true, None) if item.urgency == Some(Urgency::Low) => match i % 2 {
+        0 => app.colors.triggered_low_normal_color,
+        _ => app.colors.triggered_low_alt_color,
+      },
+      (true, None) => match i % 2 {
+        0 => app.colors.triggered_normal_color,
+        _ => app.colors.triggered_alt_color,
+      },
+      (false, _) => match i % 2 {
+        0 => app.colors.normal_row_color,
+        _ => app.colors.alt_row_color,
+      },
+    };
+    // New and changed rows stand out for a few seconds after a refresh
+    let marked = marked_ids.contains(item.id.as_str());
+    let highlighted = app.is_highlighted(&item.id);
+    let color:Color = if marked {
+      app.colors.marked_color
+    } else if highlighted {
+      app.colors.highlight_color
+    } else {
+      color
+    };
+    // Light priority colors, like yellow, need dark text
+    let fg:Color = if item.triggered() && priority_color.is_some_and(is_light) && !marked && !highlighted {
+      app.colors.buffer_bg
+    } else {
+      app.colors.row_fg
     };
-    rows.push(empty_item.ref_array().into_iter()
+    let priority_style = match priority_color {
+      Some(color) if !item.triggered() => Style::new().fg(color).add_modifier(Modifier::BOLD),
+      _ => Style::new().add_modifier(Modifier::BOLD),
+    };
+    let urgency_style = if item.urgency == Some(Urgency::High) {
+      Style::new().add_modifier(Modifier::BOLD)
+    } else {
+      Style::new().add_modifier(Modifier::DIM)
+    };
+
+    rows.push(row_cells.into_iter()
+      .enumerate()
+      .map(|(column, content)| {
+        let cell = Cell::from(Text::from(format!("\n{content}\n")));
+        match column {
+          1 => cell.style(priority_style),
+          2 => cell.style(urgency_style),
+          _ => cell,
+        }
+      })
+      .collect::<Row>()
+      .style(Style::new().fg(fg).bg(color))
+      .height(4));
+  }
+  if rows.is_empty() {
+    rows.push(["---------", "", "", " - NO INCIDENTS | TIME FOR A BREAK - ", "---------"].into_iter()
       .map(|content| Cell::from(Text::from(format!("\n{content}\n"))))
       .collect::<Row>()
       .style(Style::new().fg(app.colors.row_fg).bg(app.colors.triggered_normal_color))
@@ -80,17 +320,29 @@ pub fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
   }
 
   let bar = " █ ";
-  let title_str:String = format!(" - Pager Duty TUI - ");
+
+  // Same layout as the table, to know which header was clicked
+  let inner = area.inner(&Margin::new(1, 1));
+  let [_, columns_area] = Layout::horizontal([Constraint::Length(bar.width() as u16), Constraint::Fill(0)])
+    .areas(Rect::new(inner.x, inner.y, inner.width, 1));
+  let header_areas = Layout::horizontal(widths).spacing(1).split(columns_area);
+  app.header_columns = header_areas.iter().copied().zip(sortable).collect();
+
+  let mut title_str:String = String::from(" - Pager Duty TUI - ");
+  if let Some((_, service_name)) = &app.service_filter {
+    title_str.push_str(&format!("Service: {} - ", service_name));
+  }
+  if !app.filter.is_empty() {
+    title_str.push_str(&format!("Filter: {} - ", app.filter));
+  }
+  if let Some(column) = app.sort_by {
+    title_str.push_str(&format!("Sort: {}{} - ", column.label(), arrow));
+  }
+  if app.has_marks() {
+    title_str.push_str(&format!("Marked: {} - ", app.target_ids().len()));
+  }
   let title = Title::from(title_str.bold());
-  let t = Table::new(
-    rows.into_iter(),
-    [
-      // + 1 is for padding.
-      Constraint::Length(app.longest_item_lens.0 + 1),
-      Constraint::Min(app.longest_item_lens.1 + 1),
-      Constraint::Min(app.longest_item_lens.2),
-    ],
-  )
+  let t = Table::new(rows, widths)
   .header(header)
   .highlight_style(selected_style)
   .block(
@@ -111,7 +363,7 @@ pub fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
   f.render_stateful_widget(t, area, &mut app.state);
 }
 
-fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
+fn render_scrollbar(f: &mut Frame, state: &mut ScrollbarState, area: Rect) {
   f.render_stateful_widget(
     Scrollbar::default()
       .orientation(ScrollbarOrientation::VerticalRight)
@@ -121,30 +373,792 @@ fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
       vertical: 1,
       horizontal: 1,
     }),
-    &mut app.scroll_state,
+    state,
+  );
+}
+
+// "now until ...", "from ... until ..." or "always" for a permanent shift
+fn shift_text(oncall: &OnCall, now: &DateTime<Utc>, time_format: TimeFormat) -> String {
+  match (oncall.start, oncall.end) {
+    (_, None) => String::from("always"),
+    (_, Some(end)) if oncall.is_current(now) => format!("now until {}", format_time(&end, time_format)),
+    (Some(start), Some(end)) => format!("from {} until {}", format_time(&start, time_format), format_time(&end, time_format)),
+    (None, Some(end)) => format!("until {}", format_time(&end, time_format)),
+  }
+}
+
+fn oncalls_lines(oncalls: &[OnCall], app: &App) -> Vec<Line<'static>> {
+  let now = Utc::now();
+  let current_user_id = app.pager_duty.current_user_id();
+  let mine: Vec<&OnCall> = oncalls.iter().filter(|oncall| oncall.user_id == current_user_id).collect();
+
+  let mut lines: Vec<Line> = Vec::new();
+  let current_end = mine.iter()
+    .filter(|oncall| oncall.is_current(&now))
+    .map(|oncall| oncall.end)
+    .max_by_key(|end| end.unwrap_or(DateTime::<Utc>::MAX_UTC));
+  let next_start = mine.iter().filter_map(|oncall| oncall.start).filter(|start| *start > now).min();
+  let status = match (current_end, next_start) {
+    (Some(Some(end)), _) => format!("You are on call until {}", format_time(&end, app.time_format)),
+    (Some(None), _) => String::from("You are always on call"),
+    (None, Some(start)) => format!("You are not on call, your next shift starts {}", format_time(&start, app.time_format)),
+    (None, None) => format!("You are not on call in the next {} days", ONCALL_DAYS),
+  };
+  lines.push(Line::from(status).bold());
+  lines.push(Line::from(""));
+
+  // Policy, then level, then shifts in time order
+  let mut groups: BTreeMap<(&str, &str), BTreeMap<u64, Vec<&OnCall>>> = BTreeMap::new();
+  for oncall in oncalls {
+    groups.entry((&oncall.escalation_policy, &oncall.escalation_policy_id))
+      .or_default()
+      .entry(oncall.level)
+      .or_default()
+      .push(oncall);
+  }
+
+  let me_style = Style::new().fg(app.colors.selected_style_fg).bold();
+  for ((policy, policy_id), levels) in groups {
+    // Only the policies the user is part of, unless asked for all of them
+    if !app.all_oncalls && !mine.iter().any(|oncall| oncall.escalation_policy_id == policy_id) {
+      continue;
+    }
+    lines.push(section_title(String::from(policy)));
+    for (level, mut shifts) in levels {
+      shifts.sort_by_key(|oncall| oncall.start);
+      for oncall in shifts {
+        let schedule = oncall.schedule.as_ref().map(|schedule| format!(" ({})", schedule)).unwrap_or_default();
+        let line = Line::from(format!("  Level {}: {}{} {}", level, oncall.user, schedule, shift_text(oncall, &now, app.time_format)));
+        lines.push(if oncall.user_id == current_user_id { line.style(me_style) } else { line });
+      }
+    }
+    lines.push(Line::from(""));
+  }
+
+  lines
+}
+
+pub fn render_oncalls(f: &mut Frame, app: &App, area: Rect) {
+  let lines: Vec<Line> = match &app.oncalls {
+    None => vec![Line::from(" <- LOADING -> ")],
+    Some(Err(err)) => vec![Line::from(format!("Error while loading the on-call shifts: {}", err))],
+    Some(Ok(oncalls)) => oncalls_lines(oncalls, app),
+  };
+
+  let title_str = if app.all_oncalls {
+    format!(" - On call in the next {} days - All escalation policies - ", ONCALL_DAYS)
+  } else {
+    format!(" - On call in the next {} days - ", ONCALL_DAYS)
+  };
+  let title = Title::from(title_str.bold());
+  let oncalls = Paragraph::new(lines)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .scroll((app.oncalls_scroll, 0))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+  f.render_widget(oncalls, area);
+}
+
+fn service_status_color(status: ServiceStatus, app: &App) -> Color {
+  match status {
+    ServiceStatus::Active => app.colors.row_fg,
+    ServiceStatus::Warning => app.colors.override_color,
+    ServiceStatus::Critical => Color::Red,
+    ServiceStatus::Maintenance => app.colors.selected_style_fg,
+    ServiceStatus::Disabled => app.colors.footer_border_color,
+  }
+}
+
+pub fn render_services(f: &mut Frame, app: &mut App, area: Rect) {
+  let header = ["SERVICE", "STATUS", "TRIGGERED", "ACKNOWLEDGED", "ESCALATION POLICY"]
+    .into_iter()
+    .map(Cell::from)
+    .collect::<Row>()
+    .style(Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg));
+
+  let rows: Vec<Row> = match &app.services {
+    None => vec![Row::new([" <- LOADING -> "])],
+    Some(Err(err)) => vec![Row::new([format!("Error while loading the services: {}", err)])],
+    Some(Ok(services)) if services.is_empty() => vec![Row::new(["No services"])],
+    Some(Ok(services)) => services.iter()
+      .enumerate()
+      .map(|(i, service)| {
+        let color = if i % 2 == 0 { app.colors.normal_row_color } else { app.colors.alt_row_color };
+        let count = |count: usize| if count == 0 { Cell::from("-") } else { Cell::from(count.to_string()).bold() };
+        Row::new([
+          Cell::from(service.name.clone()),
+          Cell::from(service.status.label()).style(Style::new().fg(service_status_color(service.status, app))),
+          count(service.triggered),
+          count(service.acknowledged),
+          Cell::from(service.escalation_policy.clone().unwrap_or_default()),
+        ])
+        .style(Style::new().fg(app.colors.row_fg).bg(color))
+      })
+      .collect(),
+  };
+
+  let widths = [
+    Constraint::Fill(2),
+    Constraint::Length(12),
+    Constraint::Length(10),
+    Constraint::Length(13),
+    Constraint::Fill(1),
+  ];
+  let title = Title::from(" - Services - ".bold());
+  let t = Table::new(rows, widths)
+  .header(header)
+  .highlight_style(Style::new().add_modifier(Modifier::REVERSED).fg(app.colors.selected_style_fg))
+  .block(
+    Block::default()
+      .title(title.alignment(Alignment::Center))
+      .borders(Borders::ALL)
+      .border_style(Style::new().fg(app.colors.footer_border_color))
+      .border_type(BorderType::Rounded),
+  )
+  .highlight_symbol(" █ ")
+  .bg(app.colors.buffer_bg)
+  .highlight_spacing(HighlightSpacing::Always);
+  f.render_stateful_widget(t, area, &mut app.services_state);
+}
+
+// "ongoing until ..." or "from ... until ..."
+fn window_text(window: &MaintenanceWindow, time_format: TimeFormat) -> String {
+  let now = Utc::now();
+  let when = if window.is_ongoing(&now) {
+    format!("Ongoing until {}", format_time(&window.end, time_format))
+  } else {
+    format!("From {} until {}", format_time(&window.start, time_format), format_time(&window.end, time_format))
+  };
+  if window.description.is_empty() {
+    format!("{}: {}", when, window.services.join(", "))
+  } else {
+    format!("{}: {} ({})", when, window.services.join(", "), window.description)
+  }
+}
+
+pub fn render_maintenance_windows(f: &mut Frame, app: &App, area: Rect) {
+  let lines: Vec<Line> = match &app.maintenance_windows {
+    None => vec![Line::from(" <- LOADING -> ")],
+    Some(Err(err)) => vec![Line::from(format!("Error while loading the maintenance windows: {}", err))],
+    Some(Ok(windows)) if windows.is_empty() => vec![Line::from("No ongoing or future maintenance")],
+    Some(Ok(windows)) => windows.iter()
+      .map(|window| {
+        let line = Line::from(window_text(window, app.time_format));
+        if window.is_ongoing(&Utc::now()) { line.style(Style::new().fg(app.colors.selected_style_fg)) } else { line }
+      })
+      .collect(),
+  };
+
+  let title = Title::from(" - Maintenance windows - ".bold());
+  let windows = Paragraph::new(lines)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+  f.render_widget(windows, area);
+}
+
+pub fn render_end_maintenance(f: &mut Frame, app: &mut App) {
+  let area = centered_rect(60, 40, f.size());
+
+  let items: Vec<ListItem> = app.maintenance_windows().iter()
+    .map(|window| ListItem::new(window_text(window, app.time_format)))
+    .collect();
+  let title = Title::from(" - End a maintenance window - ".bold());
+  let list = List::new(items)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED).fg(app.colors.selected_style_fg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+
+  f.render_widget(Clear, area);
+  f.render_stateful_widget(list, area, &mut app.maintenance_state);
+}
+
+pub fn render_schedules(f: &mut Frame, app: &mut App, area: Rect) {
+  let rows: Vec<Row> = match &app.schedules {
+    None => vec![Row::new([" <- LOADING -> "])],
+    Some(Err(err)) => vec![Row::new([format!("Error while loading the schedules: {}", err)])],
+    Some(Ok(schedules)) if schedules.is_empty() => vec![Row::new(["No schedules"])],
+    Some(Ok(schedules)) => schedules.iter()
+      .map(|schedule| Row::new([schedule.name.clone()]))
+      .collect(),
+  };
+
+  let title = Title::from(" - Schedules - ".bold());
+  let t = Table::new(rows, [Constraint::Fill(1)])
+  .highlight_style(Style::new().add_modifier(Modifier::REVERSED).fg(app.colors.selected_style_fg))
+  .block(
+    Block::default()
+      .title(title.alignment(Alignment::Center))
+      .borders(Borders::ALL)
+      .border_style(Style::new().fg(app.colors.footer_border_color))
+      .border_type(BorderType::Rounded),
+  )
+  .style(Style::new().fg(app.colors.row_fg))
+  .highlight_symbol(" █ ")
+  .bg(app.colors.buffer_bg)
+  .highlight_spacing(HighlightSpacing::Always);
+  f.render_stateful_widget(t, area, &mut app.schedules_state);
+}
+
+// Width of the names in front of the bars of the timeline
+const TIMELINE_NAME_WIDTH: usize = 20;
+
+// Day names at the local midnights of the timeline, one column per slice of time
+fn timeline_header(timeline: &ScheduleTimeline, width: usize) -> (String, Vec<usize>) {
+  let slice = (timeline.until - timeline.since) / width.max(1) as i32;
+  let mut header: Vec<char> = vec![' '; width];
+  let mut day_columns: Vec<usize> = Vec::new();
+  let mut day = timeline.since.with_timezone(&Local).date_naive();
+  let mut free_from: usize = 0;
+  loop {
+    day = day.succ_opt().unwrap_or(day);
+    let Some(midnight) = day.and_hms_opt(0, 0, 0).and_then(|midnight| midnight.and_local_timezone(Local).earliest()) else {
+      break;
+    };
+    if midnight >= timeline.until || slice <= TimeDelta::zero() {
+      break;
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let column = ((midnight.with_timezone(&Utc) - timeline.since).num_seconds() / slice.num_seconds().max(1)) as usize;
+    day_columns.push(column);
+    // Labels that do not fit are left out, the marks of the days stay
+    let label: Vec<char> = midnight.format("│%a %d").to_string().chars().collect();
+    if column >= free_from && column + label.len() <= width {
+      header[column..column + label.len()].copy_from_slice(&label);
+      free_from = column + label.len() + 1;
+    }
+  }
+  (header.into_iter().collect(), day_columns)
+}
+
+// Bar of one person, with the slices of time they are on call in and the ones coming from an override
+fn timeline_bar(timeline: &ScheduleTimeline, user_id: &str, width: usize, day_columns: &[usize], app: &App) -> Vec<Span<'static>> {
+  let slice = (timeline.until - timeline.since) / width.max(1) as i32;
+  let mut spans: Vec<Span> = Vec::new();
+  let mut current: Option<(Option<bool>, String)> = None;
+  for column in 0..width {
+    let middle = timeline.since + slice * column as i32 + slice / 2;
+    let state = timeline.entries.iter()
+      .find(|entry| entry.user_id == user_id && entry.start <= middle && middle < entry.end)
+      .map(|entry| entry.override_id.is_some());
+    let symbol = match state {
+      Some(_) => '█',
+      None if day_columns.contains(&column) => '·',
+      None => ' ',
+    };
+    match &mut current {
+      Some((current_state, text)) if *current_state == state => text.push(symbol),
+      _ => {
+        if let Some((previous_state, text)) = current.take() {
+          spans.push(timeline_span(previous_state, text, app));
+        }
+        current = Some((state, String::from(symbol)));
+      }
+    }
+  }
+  if let Some((state, text)) = current {
+    spans.push(timeline_span(state, text, app));
+  }
+  spans
+}
+
+fn timeline_span(state: Option<bool>, text: String, app: &App) -> Span<'static> {
+  match state {
+    Some(true) => Span::styled(text, Style::new().fg(app.colors.override_color)),
+    Some(false) => Span::styled(text, Style::new().fg(app.colors.selected_style_fg)),
+    None => Span::styled(text, Style::new().fg(app.colors.footer_border_color)),
+  }
+}
+
+fn entry_text(entry: &ScheduleEntry, time_format: TimeFormat) -> String {
+  let source = if entry.override_id.is_some() { " (override)" } else { "" };
+  format!("  {}{}: {} until {}", entry.user, source, format_time(&entry.start, time_format), format_time(&entry.end, time_format))
+}
+
+fn timeline_lines(timeline: &ScheduleTimeline, width: usize, app: &App) -> Vec<Line<'static>> {
+  let bar_width = width.saturating_sub(TIMELINE_NAME_WIDTH + 1);
+  let (header, day_columns) = timeline_header(timeline, bar_width);
+  let current_user_id = app.pager_duty.current_user_id();
+
+  let mut lines: Vec<Line> = vec![Line::from(format!("{:width$} {}", "", header, width = TIMELINE_NAME_WIDTH))];
+
+  // One bar per person, in the order of their first shift
+  let mut people: Vec<(&str, &str)> = Vec::new();
+  for entry in &timeline.entries {
+    if !people.iter().any(|(user_id, _)| *user_id == entry.user_id) {
+      people.push((&entry.user_id, &entry.user));
+    }
+  }
+  if people.is_empty() {
+    lines.push(Line::from("Nobody is on call in this schedule"));
+  }
+  let me_style = Style::new().fg(app.colors.selected_style_fg).bold();
+  for (user_id, user) in people {
+    let name: String = format!("{:width$}", user, width = TIMELINE_NAME_WIDTH).chars().take(TIMELINE_NAME_WIDTH).collect();
+    let mut spans = vec![
+      if user_id == current_user_id { Span::styled(name, me_style) } else { Span::raw(name) },
+      Span::raw(" "),
+    ];
+    spans.extend(timeline_bar(timeline, user_id, bar_width, &day_columns, app));
+    lines.push(Line::from(spans));
+  }
+
+  lines.push(Line::from(""));
+  lines.push(Line::from(vec![
+    Span::styled("█", Style::new().fg(app.colors.selected_style_fg)),
+    Span::raw(" Shift  "),
+    Span::styled("█", Style::new().fg(app.colors.override_color)),
+    Span::raw(" Override"),
+  ]));
+  lines.push(Line::from(""));
+
+  if !timeline.overrides.is_empty() {
+    lines.push(section_title(String::from("Overrides")));
+    for schedule_override in &timeline.overrides {
+      lines.push(Line::from(entry_text(schedule_override, app.time_format)));
+    }
+    lines.push(Line::from(""));
+  }
+
+  lines.push(section_title(String::from("Shifts")));
+  for entry in &timeline.entries {
+    let line = Line::from(entry_text(entry, app.time_format));
+    lines.push(if entry.user_id == current_user_id { line.style(me_style) } else { line });
+  }
+  lines
+}
+
+pub fn render_timeline(f: &mut Frame, app: &App, area: Rect) {
+  // Borders and padding
+  let width = usize::from(area.width.saturating_sub(4));
+  let lines: Vec<Line> = match (&app.timeline, app.selected_schedule()) {
+    (_, None) => Vec::new(),
+    (None, Some(_)) => vec![Line::from(" <- LOADING -> ")],
+    (Some(Err(err)), Some(_)) => vec![Line::from(format!("Error while loading the schedule: {}", err))],
+    (Some(Ok(timeline)), Some(_)) => timeline_lines(timeline, width, app),
+  };
+
+  let title_str = match app.selected_schedule() {
+    Some(schedule) if !schedule.time_zone.is_empty() => format!(" - {} ({}) - Next {} days - ", schedule.name, schedule.time_zone, app.timeline_days),
+    Some(schedule) => format!(" - {} - Next {} days - ", schedule.name, app.timeline_days),
+    None => String::from(" - Timeline - "),
+  };
+  let title = Title::from(title_str.bold());
+  let timeline = Paragraph::new(lines)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+  f.render_widget(timeline, area);
+}
+
+fn section_title(title: String) -> Line<'static> {
+  Line::from(format!("── {} ──", title)).bold()
+}
+
+fn details_lines(details: &IncidentDetails, time_format: TimeFormat) -> Vec<Line<'static>> {
+  let mut lines: Vec<Line> = vec![
+    Line::from(format!("{} {}", format_number(details.number), details.summary)).bold(),
+    Line::from(format!("Status: {} | Urgency: {}", details.status.label(), details.urgency.map(|urgency| urgency.label()).unwrap_or_default())),
+    Line::from(format!("Service: {}", details.service)),
+    Line::from(format!("Created at: {}", format_time(&details.created_at, time_format))),
+    Line::from(format!("Assigned to: {}", details.assignees.join(", "))),
+    Line::from(""),
+  ];
+
+  if !details.details.is_empty() {
+    lines.push(section_title(String::from("Details")));
+    lines.extend(details.details.lines().map(|line| Line::from(String::from(line))));
+    lines.push(Line::from(""));
+  }
+
+  lines.push(section_title(format!("Alerts ({})", details.alerts.len())));
+  for alert in &details.alerts {
+    lines.push(Line::from(format!("{} [{}] {}", format_time(&alert.created_at, time_format), alert.status, alert.summary)));
+  }
+  lines.push(Line::from(""));
+
+  lines.push(section_title(format!("Timeline ({})", details.log_entries.len())));
+  for log_entry in &details.log_entries {
+    lines.push(Line::from(format!("{} {}", format_time(&log_entry.created_at, time_format), log_entry.summary)));
+  }
+  lines.push(Line::from(""));
+
+  lines.push(section_title(format!("Notes ({})", details.notes.len())));
+  for note in &details.notes {
+    lines.push(Line::from(format!("{} {}", format_time(&note.created_at, time_format), note.user)).italic());
+    lines.extend(note.content.lines().map(|line| Line::from(format!("  {}", line))));
+  }
+
+  lines
+}
+
+pub fn render_details(f: &mut Frame, app: &App, area: Rect) {
+  let lines: Vec<Line> = match &app.details {
+    None => vec![Line::from(" <- LOADING -> ")],
+    Some(Err(err)) => vec![Line::from(format!("Error while loading the incident: {}", err))],
+    Some(Ok(details)) => details_lines(details, app.time_format),
+  };
+
+  let title = Title::from(" - Incident details - ".bold());
+  let details = Paragraph::new(lines)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .wrap(Wrap { trim: false })
+    .scroll((app.details_scroll, 0))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+  f.render_widget(details, area);
+}
+
+pub fn render_note_composer(f: &mut Frame, app: &App) {
+  let area = centered_rect(60, 50, f.size());
+
+  let title_str = match app.target_incident_ids.len() {
+    1 => String::from(" - New note - "),
+    count => format!(" - New note on {} incidents - ", count),
+  };
+  let title = Title::from(title_str.bold());
+  let composer = Paragraph::new(app.input.as_str())
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+  f.render_widget(Clear, area);
+  f.render_widget(composer, area);
+
+  // Cursor at the end of the text
+  let line_count = app.input.split('\n').count();
+  let last_line = app.input.split('\n').next_back().unwrap_or("");
+  #[allow(clippy::cast_possible_truncation)]
+  f.set_cursor(
+    area.x + 2 + last_line.width() as u16,
+    area.y + line_count as u16,
   );
 }
 
+pub fn render_picker(f: &mut Frame, app: &mut App) {
+  let area = centered_rect(50, 60, f.size());
+  let rects = Layout::vertical([Constraint::Length(3), Constraint::Min(3)]).split(area);
+
+  let search = Paragraph::new(app.input.as_str())
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(
+      Block::default()
+        .title(Title::from(app.picker.title.as_str().bold()).alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+
+  let items: Vec<ListItem> = match &app.picker.entries {
+    None => vec![ListItem::new(" <- LOADING -> ")],
+    Some(Err(err)) => vec![ListItem::new(format!("Error while loading: {}", err))],
+    Some(Ok(_)) => app.picker.filtered(&app.input).into_iter()
+      .map(|entry| {
+        let kind = match entry.kind {
+          AssigneeKind::User => "User",
+          AssigneeKind::EscalationPolicy => "Escalation policy",
+        };
+        ListItem::new(Line::from(vec![
+          Span::from(entry.name.clone()),
+          Span::from(format!("  ({})", kind)).italic(),
+        ]))
+      })
+      .collect(),
+  };
+  let list = List::new(items)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED).fg(app.colors.selected_style_fg))
+    .block(
+      Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded),
+    );
+
+  f.render_widget(Clear, area);
+  f.render_widget(search, rects[0]);
+  f.render_stateful_widget(list, rects[1], &mut app.picker.state);
+
+  #[allow(clippy::cast_possible_truncation)]
+  f.set_cursor(rects[0].x + 2 + app.input.width() as u16, rects[0].y + 1);
+}
+
+pub fn render_duration_prompt(f: &mut Frame, app: &mut App, title_str: String) {
+  let area = centered_rect(30, 30, f.size());
+
+  let mut items: Vec<ListItem> = DURATIONS.iter()
+    .map(|(label, _)| ListItem::new(*label))
+    .collect();
+  items.push(ListItem::new(format!("Custom: {}", app.input)));
+
+  let title = Title::from(title_str.bold());
+  let list = List::new(items)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED).fg(app.colors.selected_style_fg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+
+  f.render_widget(Clear, area);
+  f.render_stateful_widget(list, area, &mut app.duration_state);
+}
+
+// The typed time as it is understood, or why it is not
+fn time_preview(text: &str, now: &DateTime<Local>, time_format: TimeFormat) -> String {
+  match parse_local_time(text, now) {
+    Some(time) => format!("    {}", format_time(&time, time_format)),
+    None => String::from("    Unknown time"),
+  }
+}
+
+pub fn render_override_form(f: &mut Frame, app: &App) {
+  let Some(form) = &app.override_form else {
+    return;
+  };
+  let area = centered_rect(50, 40, f.size());
+  let now = Local::now();
+
+  let field_style = |editing: bool| if editing {
+    Style::new().fg(app.colors.selected_style_fg).bold()
+  } else {
+    Style::new()
+  };
+  let mut lines: Vec<Line> = vec![
+    Line::from(format!("On call: {}", form.user.name)),
+    Line::from(""),
+    Line::from(format!("Start: {}", form.start)).style(field_style(!form.editing_end)),
+    Line::from(time_preview(&form.start, &now, app.time_format)).italic(),
+    Line::from(format!("End:   {}", form.end)).style(field_style(form.editing_end)),
+    Line::from(time_preview(&form.end, &now, app.time_format)).italic(),
+  ];
+  if let Some(error) = &form.error {
+    lines.push(Line::from(""));
+    lines.push(Line::from(error.clone()).fg(Color::Red));
+  }
+
+  let schedule_name = app.selected_schedule().map(|schedule| schedule.name.as_str()).unwrap_or_default();
+  let title = Title::from(format!(" - New override in {} - ", schedule_name).bold());
+  let form_widget = Paragraph::new(lines)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+  f.render_widget(Clear, area);
+  f.render_widget(form_widget, area);
+
+  // Cursor at the end of the field being typed
+  let (label, text, line) = if form.editing_end { ("End:   ", &form.end, 5) } else { ("Start: ", &form.start, 3) };
+  #[allow(clippy::cast_possible_truncation)]
+  f.set_cursor(area.x + 2 + (label.width() + text.width()) as u16, area.y + line);
+}
+
+pub fn render_delete_override(f: &mut Frame, app: &mut App) {
+  let area = centered_rect(50, 40, f.size());
+
+  let items: Vec<ListItem> = app.own_overrides().into_iter()
+    .map(|schedule_override| ListItem::new(format!("{} until {}",
+      format_time(&schedule_override.start, app.time_format),
+      format_time(&schedule_override.end, app.time_format))))
+    .collect();
+  let title = Title::from(" - Delete one of your overrides - ".bold());
+  let list = List::new(items)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED).fg(app.colors.selected_style_fg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+
+  f.render_widget(Clear, area);
+  f.render_stateful_widget(list, area, &mut app.override_state);
+}
+
+pub fn render_confirmation(f: &mut Frame, app: &App) {
+  let Some(confirmation) = &app.confirmation else {
+    return;
+  };
+  let area = centered_rect(60, 50, f.size());
+
+  let verb = match &confirmation.action {
+    ConfirmAction::Acknowledge => String::from("Acknowledge"),
+    ConfirmAction::Resolve => String::from("Resolve"),
+    ConfirmAction::Escalate => String::from("Escalate"),
+    ConfirmAction::Snooze(duration) => format!("Snooze for {}", format_duration(*duration)),
+    ConfirmAction::Reassign(assignee) => format!("Reassign to {}", assignee.name),
+    ConfirmAction::Note(_) => String::from("Add the note to"),
+    ConfirmAction::EndMaintenance(_) => String::from("End"),
+    ConfirmAction::DeleteOverride { .. } => String::from("Delete"),
+  };
+  let title_str = match (&confirmation.action, confirmation.incident_ids.len()) {
+    (ConfirmAction::EndMaintenance(_), _) => format!(" - {} this maintenance window? - ", verb),
+    (ConfirmAction::DeleteOverride { .. }, _) => format!(" - {} this override? - ", verb),
+    (_, 1) => format!(" - {} this incident? - ", verb),
+    (_, count) => format!(" - {} {} incidents? - ", verb, count),
+  };
+
+  let lines: Vec<Line> = match &confirmation.action {
+    ConfirmAction::EndMaintenance(window_id) => app.maintenance_windows().iter()
+      .filter(|window| &window.id == window_id)
+      .map(|window| Line::from(window_text(window, app.time_format)))
+      .collect(),
+    ConfirmAction::DeleteOverride { override_id, .. } => app.own_overrides().into_iter()
+      .filter(|schedule_override| schedule_override.override_id.as_ref() == Some(override_id))
+      .map(|schedule_override| Line::from(format!("{} until {}",
+        format_time(&schedule_override.start, app.time_format),
+        format_time(&schedule_override.end, app.time_format))))
+      .collect(),
+    // Incidents that are not in the list anymore are shown by id
+    _ => confirmation.incident_ids.iter()
+      .map(|id| match app.items.iter().find(|item| &item.id == id) {
+        Some(item) => Line::from(format!("{} {} - {}", format_number(item.number), item.service, item.summary)),
+        None => Line::from(id.clone()),
+      })
+      .collect(),
+  };
+
+  let title = Title::from(title_str.bold());
+  let list = Paragraph::new(lines)
+    .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(
+      Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(app.colors.triggered_alt_color))
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1)),
+    );
+
+  f.render_widget(Clear, area);
+  f.render_widget(list, area);
+}
+
+pub fn render_error(f: &mut Frame, app: &App, area: Rect) {
+  if let Some(error) = &app.error {
+    let error_banner = Paragraph::new(Line::from(format!(" /!\\ {} ", error)))
+      .style(Style::new().fg(app.colors.row_fg).bg(app.colors.triggered_normal_color))
+      .centered()
+      .block(
+        Block::default()
+          .borders(Borders::ALL)
+          .border_style(Style::new().fg(app.colors.triggered_alt_color))
+          .border_type(BorderType::Rounded)
+          .bold(),
+      );
+    f.render_widget(error_banner, area);
+  }
+}
+
 pub fn render_footer(f: &mut Frame, app: &App, area: Rect) {
   let footer_text: String;
   let color_bg: Color;
   let border: Borders;
   let padding: Padding;
-  if app.refreshing {
+  let mut alignment = Alignment::Center;
+  if app.mode == Mode::Search {
+    border = Borders::ALL;
+    footer_text = format!("/{}", app.input);
+    color_bg = app.colors.buffer_bg;
+    padding = Padding::horizontal(1);
+    alignment = Alignment::Left;
+    #[allow(clippy::cast_possible_truncation)]
+    f.set_cursor(area.x + 3 + app.input.width() as u16, area.y + 1);
+  } else if let Some((retry, seconds)) = app.retry_in() {
+    border = Borders::NONE;
+    footer_text = if retry.status == StatusCode::TOO_MANY_REQUESTS {
+      format!(" <- RATE LIMITED, RETRYING IN {}s -> ", seconds)
+    } else {
+      format!(" <- PAGERDUTY ERROR {}, RETRYING IN {}s -> ", retry.status.as_u16(), seconds)
+    };
+    color_bg = Color::Yellow;
+    padding = Padding::new(0, 0, 1, 0);
+  } else if app.refreshing {
     border = Borders::NONE;
     footer_text = String::from(" <- REFRESHING -> ");
     color_bg = Color::Yellow;
     padding = Padding::new(0, 0, 1, 0);
   } else {
     border = Borders::ALL;
-    footer_text = String::from(INFO_TEXT);
+    footer_text = match app.mode {
+      Mode::Normal if app.tab == Tab::OnCall => String::from(ONCALL_INFO_TEXT),
+      Mode::Normal if app.tab == Tab::Services => String::from(SERVICES_INFO_TEXT),
+      Mode::Normal if app.tab == Tab::Schedules => String::from(SCHEDULES_INFO_TEXT),
+      Mode::Normal => String::from(INFO_TEXT),
+      Mode::Detail => String::from(DETAIL_INFO_TEXT),
+      Mode::Note => String::from(NOTE_INFO_TEXT),
+      Mode::Picker => String::from(PICKER_INFO_TEXT),
+      Mode::Snooze => String::from(SNOOZE_INFO_TEXT),
+      Mode::Search => String::new(),
+      Mode::Confirm => String::from(CONFIRM_INFO_TEXT),
+      Mode::OverrideForm => String::from(OVERRIDE_FORM_INFO_TEXT),
+      Mode::Maintenance => String::from(MAINTENANCE_INFO_TEXT),
+      Mode::EndMaintenance => String::from(END_MAINTENANCE_INFO_TEXT),
+      Mode::DeleteOverride => String::from(DELETE_OVERRIDE_INFO_TEXT),
+    };
     color_bg = app.colors.buffer_bg;
     padding = Padding::new(0, 0, 0, 0);
   }
 
-  let info_footer = Paragraph::new(Line::from(footer_text))
+  let mut spans: Vec<Span> = Vec::new();
+  if !app.unseen.is_empty() && border == Borders::ALL && app.mode != Mode::Search {
+    spans.push(Span::styled(format!(" {} new ", app.unseen.len()), Style::new().bg(app.colors.highlight_color)));
+    spans.push(Span::raw(" "));
+  }
+  spans.push(Span::raw(footer_text));
+
+  let info_footer = Paragraph::new(Line::from(spans))
     .style(Style::new().fg(app.colors.row_fg).bg(color_bg))
-    .centered()
+    .alignment(alignment)
     .block(
       Block::default()
         .borders(border)
@@ -1,5 +1,22 @@
 use std::process::Command;
 use  std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday};
+use serde::Deserialize;
+
+// How timestamps are shown, saved in the config as `time_format`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+  // "12m ago"
+  Relative,
+  // "2024-03-01 11:00:00" in the timezone of the computer
+  Local,
+  // "2024-03-01 11:00:00 (12m ago)"
+  #[default]
+  Both,
+}
 
 pub fn split_str(text:String, lenght:u16) -> String{
   let mut result:String = String::from("");
@@ -12,6 +29,93 @@ pub fn split_str(text:String, lenght:u16) -> String{
   format!("{}...",result)
 }
 
+// Read a duration like "90", "90m", "2h" or "1d", without unit it is minutes
+pub fn parse_duration(text: &str) -> Option<u64> {
+  let text = text.trim().to_lowercase();
+  let (value, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+    Some(i) => text.split_at(i),
+    None => (text.as_str(), "m"),
+  };
+  let value: u64 = value.parse().ok()?;
+  let unit_seconds: u64 = match unit.trim() {
+    "s" => 1,
+    "m" | "min" => 60,
+    "h" => 3600,
+    "d" => 86400,
+    _ => return None,
+  };
+  let seconds = value.checked_mul(unit_seconds)?;
+  if seconds > 0 { Some(seconds) } else { None }
+}
+
+// Format of the times typed in the override form, and of the ones filled in for the user
+pub const TIME_INPUT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+// Read a time in the timezone of the computer like "2024-03-01 18:00", "Fri 18:00" or "18:00",
+// without a date it is the next time it happens
+pub fn parse_local_time(text: &str, now: &DateTime<Local>) -> Option<DateTime<Utc>> {
+  let text = text.trim();
+  let naive = match NaiveDateTime::parse_from_str(text, TIME_INPUT_FORMAT) {
+    Ok(naive) => naive,
+    Err(_) => {
+      let (day, time) = match text.split_once(' ') {
+        Some((day, time)) => (Some(day.parse::<Weekday>().ok()?), time),
+        None => (None, text),
+      };
+      let time = NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?;
+      let today = now.date_naive();
+      // Days until the given day of the week, or until tomorrow when the time already passed today
+      let mut days = match day {
+        Some(day) => i64::from((7 + day.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7),
+        None => 0,
+      };
+      if days == 0 && time <= now.time() {
+        days = if day.is_some() { 7 } else { 1 };
+      }
+      (today + TimeDelta::days(days)).and_time(time)
+    }
+  };
+  naive.and_local_timezone(Local).earliest().map(|time| time.with_timezone(&Utc))
+}
+
+// Duration like "45s", "12m", "2h 5m" or "3d"
+pub fn format_duration(seconds: u64) -> String {
+  match seconds {
+    0..=59 => format!("{}s", seconds),
+    60..=3599 => format!("{}m", seconds / 60),
+    3600..=86399 if seconds % 3600 >= 60 => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    3600..=86399 => format!("{}h", seconds / 3600),
+    _ => format!("{}d", seconds / 86400),
+  }
+}
+
+// Time between now and a timestamp, like "12m ago" or "in 2h 5m"
+pub fn format_age(time: &DateTime<Utc>, now: &DateTime<Utc>) -> String {
+  let seconds = now.signed_duration_since(*time).num_seconds();
+  let text = format_duration(seconds.unsigned_abs());
+  if seconds >= 0 { format!("{} ago", text) } else { format!("in {}", text) }
+}
+
+pub fn format_local(time: &DateTime<Utc>) -> String {
+  time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+// Timestamp as chosen in the config, the age changes as time goes by so it is worked out on each draw
+pub fn format_time(time: &DateTime<Utc>, time_format: TimeFormat) -> String {
+  let now = Utc::now();
+  match time_format {
+    TimeFormat::Relative => format_age(time, &now),
+    TimeFormat::Local => format_local(time),
+    TimeFormat::Both => format!("{} ({})", format_local(time), format_age(time, &now)),
+  }
+}
+
+// Random enough number of milliseconds below max_ms, to spread retries
+pub fn jitter(max_ms: u64) -> u64 {
+  let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+  u64::from(nanos) % max_ms.max(1)
+}
+
 pub fn open_in_browser(url:&str){
   let os = env::consts::OS;
   
@@ -23,7 +127,99 @@ pub fn open_in_browser(url:&str){
     let _ = Command::new("/usr/bin/open")
       .arg(url)
       .output();
-  } else {
-    ()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  // Wednesday
+  fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2024, 3, 6, 12, 0, 0).unwrap()
+  }
+
+  fn local(day: u32, hour: u32, minute: u32) -> Option<DateTime<Utc>> {
+    Some(Local.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap().with_timezone(&Utc))
+  }
+
+  #[test]
+  fn parse_duration_units() {
+    assert_eq!(parse_duration("90"), Some(5400));
+    assert_eq!(parse_duration("45s"), Some(45));
+    assert_eq!(parse_duration("90m"), Some(5400));
+    assert_eq!(parse_duration("90min"), Some(5400));
+    assert_eq!(parse_duration(" 2H "), Some(7200));
+    assert_eq!(parse_duration("1d"), Some(86400));
+  }
+
+  #[test]
+  fn parse_duration_rejects_bad_input() {
+    assert_eq!(parse_duration(""), None);
+    assert_eq!(parse_duration("0m"), None);
+    assert_eq!(parse_duration("2w"), None);
+    assert_eq!(parse_duration("h"), None);
+    assert_eq!(parse_duration("-5m"), None);
+  }
+
+  #[test]
+  fn parse_duration_overflow() {
+    assert_eq!(parse_duration("99999999999999999d"), None);
+    assert_eq!(parse_duration("99999999999999999999"), None);
+  }
+
+  #[test]
+  fn parse_local_time_full_date() {
+    assert_eq!(parse_local_time("2024-03-01 18:00", &now()), local(1, 18, 0));
+  }
+
+  #[test]
+  fn parse_local_time_without_date() {
+    assert_eq!(parse_local_time("18:00", &now()), local(6, 18, 0));
+    // Already passed today
+    assert_eq!(parse_local_time("11:00", &now()), local(7, 11, 0));
+    assert_eq!(parse_local_time("12:00", &now()), local(7, 12, 0));
+  }
+
+  #[test]
+  fn parse_local_time_weekday() {
+    assert_eq!(parse_local_time("Fri 18:00", &now()), local(8, 18, 0));
+    assert_eq!(parse_local_time("Wed 13:00", &now()), local(6, 13, 0));
+  }
+
+  #[test]
+  fn parse_local_time_weekday_wraps_around() {
+    assert_eq!(parse_local_time("Mon 09:00", &now()), local(11, 9, 0));
+    assert_eq!(parse_local_time("Wed 11:00", &now()), local(13, 11, 0));
+  }
+
+  #[test]
+  fn parse_local_time_rejects_bad_input() {
+    assert_eq!(parse_local_time("", &now()), None);
+    assert_eq!(parse_local_time("25:00", &now()), None);
+    assert_eq!(parse_local_time("Someday 18:00", &now()), None);
+    assert_eq!(parse_local_time("2024-02-30 18:00", &now()), None);
+  }
+
+  #[test]
+  fn format_duration_units() {
+    assert_eq!(format_duration(0), "0s");
+    assert_eq!(format_duration(45), "45s");
+    assert_eq!(format_duration(720), "12m");
+    assert_eq!(format_duration(7200), "2h");
+    assert_eq!(format_duration(7500), "2h 5m");
+    // Seconds left over are not shown
+    assert_eq!(format_duration(7230), "2h");
+    assert_eq!(format_duration(3 * 86400 + 3600), "3d");
+  }
+
+  #[test]
+  fn format_age_past_and_future() {
+    let now = Utc.with_ymd_and_hms(2024, 3, 6, 12, 0, 0).unwrap();
+    assert_eq!(format_age(&(now - TimeDelta::minutes(12)), &now), "12m ago");
+    assert_eq!(format_age(&now, &now), "0s ago");
+    assert_eq!(format_age(&(now + TimeDelta::minutes(125)), &now), "in 2h 5m");
   }
 }